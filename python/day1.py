@@ -18,7 +18,7 @@ def part_1(data):
 def part_2(data):
     for index, number in enumerate(data):
         result = two_sum(data[index+1:], 2020 - number)
-        if result:
+        if result is not None:
             return number * result
 
 
@@ -15,8 +15,8 @@ def perform_move(circle: List[int], current_cup: int, wrap_value: int) -> int:
     destination = current_cup - 1 if current_cup != 1 else wrap_value
     while destination == pick_1 or destination == pick_3 or destination == pick_2:
         destination -= 1
-    if destination == 0:
-        destination = wrap_value
+        if destination == 0:
+            destination = wrap_value
 
     # insert the 3 cups picked up after the destination, so the next cup of
     # the destination cup will be the first picked up cup, and the next cup
@@ -0,0 +1,37 @@
+//! Hashes the sources of the solvers into `AOC2020_SOLVER_HASH`, which keys
+//! the cached answers: editing a solver must not leave its old answers behind.
+
+use std::env;
+use std::fs;
+use std::path::Path;
+
+/// Sources of the binary only, which cannot change any answer
+const BINARY_SOURCES: [&str; 2] = ["main.rs", "cli.rs"];
+
+/// The 64-bit FNV-1a hash, as in `cache::fnv1a`.
+fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(hash, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+fn main() {
+    let src = Path::new(&env::var("CARGO_MANIFEST_DIR").unwrap()).join("src");
+    println!("cargo:rerun-if-changed={}", src.display());
+    let mut paths: Vec<_> = fs::read_dir(&src)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension() == Some("rs".as_ref()))
+        .filter(|path| {
+            let name = path.file_name().unwrap().to_string_lossy();
+            !BINARY_SOURCES.contains(&name.as_ref())
+        })
+        .collect();
+    paths.sort();
+    let mut hash = 0xcbf2_9ce4_8422_2325;
+    for path in paths {
+        hash = fnv1a(hash, path.file_name().unwrap().to_string_lossy().as_bytes());
+        hash = fnv1a(hash, &fs::read(&path).unwrap());
+    }
+    println!("cargo:rustc-env=AOC2020_SOLVER_HASH={:016x}", hash);
+}
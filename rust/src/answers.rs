@@ -0,0 +1,201 @@
+use std::collections::HashMap;
+
+use crate::error::{Error, Result};
+use crate::runner::DayReport;
+use crate::solution::Answer;
+use crate::utilities::{map_lines, parse_number, split_pair};
+
+/// Expected answers, read from a TOML file holding one table per day:
+///
+/// ```toml
+/// [day1]
+/// part1 = 444019
+/// part2 = "29212176"
+/// ```
+#[derive(Debug, Default)]
+pub struct Answers {
+    answers: HashMap<(usize, u8), String>,
+}
+
+impl Answers {
+    /// Only the subset of TOML above is supported: tables, comments, and
+    /// string or integer values.
+    pub fn parse(input: &str) -> Result<Answers> {
+        Self::parse_tables(input, None)
+    }
+
+    /// Reads the answers of `day` alone, which can leave out its table.
+    pub fn parse_day(day: usize, input: &str) -> Result<Answers> {
+        Self::parse_tables(input, Some(day))
+    }
+
+    fn parse_tables(input: &str, mut day: Option<usize>) -> Result<Answers> {
+        let mut answers = HashMap::new();
+        map_lines(input, |line| {
+            let content = strip_comment(line).trim();
+            if content.is_empty() {
+                return Ok(());
+            }
+            if let Some(table) = content.strip_prefix('[') {
+                let table = table
+                    .strip_suffix(']')
+                    .and_then(|table| table.trim().strip_prefix("day"))
+                    .ok_or_else(|| {
+                        Error::parse(format!(
+                            "expected a table such as `[day1]`, found `{}`",
+                            content
+                        ))
+                        .at(line, content)
+                    })?;
+                day = Some(parse_number(line, table)?);
+                return Ok(());
+            }
+            let (key, value) = split_pair(line, content, "=")?;
+            let part = match key.trim() {
+                "part1" => 1,
+                "part2" => 2,
+                _ => {
+                    return Err(Error::parse(format!(
+                        "unknown key `{}`, expected part1 or part2",
+                        key.trim()
+                    ))
+                    .at(line, key))
+                }
+            };
+            let day = day.ok_or_else(|| Error::parse("the answer does not belong to any day"))?;
+            let value = value.trim();
+            let answer = match value.strip_prefix('"') {
+                Some(answer) => answer
+                    .strip_suffix('"')
+                    .ok_or_else(|| Error::parse("unterminated string").at(line, value))?,
+                None => {
+                    parse_number::<i64>(line, value)?;
+                    value
+                }
+            };
+            if answers.insert((day, part), answer.to_string()).is_some() {
+                return Err(Error::parse(format!(
+                    "part {} of day {} is given twice",
+                    part, day
+                )));
+            }
+            Ok(())
+        })?;
+        Ok(Answers { answers })
+    }
+
+    pub fn get(&self, day: usize, part: u8) -> Option<&str> {
+        self.answers.get(&(day, part)).map(String::as_str)
+    }
+}
+
+/// Removes a `#` comment, which does not start inside a quoted answer.
+fn strip_comment(line: &str) -> &str {
+    let mut quoted = false;
+    for (index, c) in line.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            '#' if !quoted => return &line[..index],
+            _ => {}
+        }
+    }
+    line
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Verdict {
+    Pass,
+    Mismatch {
+        expected: String,
+        actual: String,
+    },
+    /// The solver failed with the given error
+    Fail(String),
+    /// There is no expected answer to compare with
+    Unknown(String),
+}
+
+impl Verdict {
+    pub fn is_failure(&self) -> bool {
+        matches!(self, Verdict::Mismatch { .. } | Verdict::Fail(_))
+    }
+}
+
+/// Compares every part of a report with its expected answer. A failed parse
+/// gives a single verdict with no part.
+pub fn verify(report: &DayReport, answers: &Answers) -> Vec<(Option<u8>, Verdict)> {
+    let parts = match &report.parts {
+        Ok(parts) => parts,
+        Err(error) => return vec![(None, Verdict::Fail(error.to_string()))],
+    };
+    parts
+        .iter()
+        .map(|part| {
+            let actual = part.answer.as_ref().map(Answer::to_string);
+            let verdict = match (actual, answers.get(report.day, part.part)) {
+                (Err(error), _) => Verdict::Fail(error.to_string()),
+                (Ok(actual), None) => Verdict::Unknown(actual),
+                (Ok(actual), Some(expected)) if actual == expected => Verdict::Pass,
+                (Ok(actual), Some(expected)) => Verdict::Mismatch {
+                    expected: expected.to_string(),
+                    actual,
+                },
+            };
+            (Some(part.part), verdict)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::run_day;
+
+    #[test]
+    fn test_parse_answers() {
+        let answers = Answers::parse(
+            "# Expected answers\n[day1]\npart1 = 514579\n\n[ day21 ]\npart2 = \"mx,sq # fv\" # comment",
+        )
+        .unwrap();
+        assert_eq!(answers.get(1, 1), Some("514579"));
+        assert_eq!(answers.get(1, 2), None);
+        assert_eq!(answers.get(21, 2), Some("mx,sq # fv"));
+
+        let error = Answers::parse("[day1]\npart3 = 1").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 2, column 1: unknown key `part3`, expected part1 or part2"
+        );
+        assert!(Answers::parse("part1 = 1").is_err());
+        assert!(Answers::parse("[day1]\npart1 = abc").is_err());
+        assert!(Answers::parse("[day1]\npart1 = 1\npart1 = 2").is_err());
+
+        let answers = Answers::parse_day(5, "part1 = 820").unwrap();
+        assert_eq!(answers.get(5, 1), Some("820"));
+    }
+
+    #[test]
+    fn test_verify() {
+        let answers = Answers::parse("[day1]\npart1 = 514579\npart2 = 1").unwrap();
+        let report = run_day(1, None, "1721\n979\n366\n299\n675\n1456");
+        assert_eq!(
+            verify(&report, &answers),
+            vec![
+                (Some(1), Verdict::Pass),
+                (
+                    Some(2),
+                    Verdict::Mismatch {
+                        expected: "1".to_string(),
+                        actual: "241861950".to_string()
+                    }
+                ),
+            ]
+        );
+
+        let report = run_day(2, Some(1), "1-3 a: abcde");
+        assert_eq!(
+            verify(&report, &answers),
+            vec![(Some(1), Verdict::Unknown("1".to_string()))]
+        );
+    }
+}
@@ -0,0 +1,497 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::hash::Hash;
+use std::str::FromStr;
+
+use crate::error::{Error, Result};
+use crate::grid::{Grid, Position};
+use crate::{day11, day17, day24};
+
+/// Days whose puzzle is a cellular automaton, see `automaton`.
+pub const DAYS: [usize; 3] = [11, 17, 24];
+
+/// A life-like rule, giving the numbers of live neighbours that bring a dead
+/// cell to life (birth) and keep a live one alive (survival).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rule {
+    birth: u32,
+    survival: u32,
+}
+
+const fn mask(counts: &[u8]) -> u32 {
+    let mut mask = 0;
+    let mut index = 0;
+    while index < counts.len() {
+        mask |= 1 << counts[index];
+        index += 1;
+    }
+    mask
+}
+
+impl Rule {
+    /// Conway's Game of Life, `B3/S23`
+    pub const LIFE: Rule = Rule::new(&[3], &[2, 3]);
+
+    /// Neighbour counts go up to 31.
+    pub const fn new(birth: &[u8], survival: &[u8]) -> Rule {
+        Rule {
+            birth: mask(birth),
+            survival: mask(survival),
+        }
+    }
+
+    /// Whether a cell is alive in the next generation.
+    pub fn next(&self, alive: bool, neighbors: usize) -> bool {
+        let counts = if alive { self.survival } else { self.birth };
+        neighbors < 32 && counts & (1 << neighbors) != 0
+    }
+}
+
+/// Parses rules written `B3/S23`, each count being a single digit.
+impl FromStr for Rule {
+    type Err = Error;
+
+    fn from_str(rule: &str) -> Result<Rule> {
+        let mut birth = None;
+        let mut survival = None;
+        for part in rule.split('/') {
+            let (counts, name) = match part.chars().next() {
+                Some('B') | Some('b') => (&mut birth, "birth"),
+                Some('S') | Some('s') => (&mut survival, "survival"),
+                _ => {
+                    return Err(Error::parse(format!(
+                        "expected a rule such as `B3/S23`, found `{}`",
+                        rule
+                    )))
+                }
+            };
+            if counts.is_some() {
+                return Err(Error::parse(format!("the {} counts are given twice", name)));
+            }
+            let mut mask = 0;
+            for (index, digit) in part[1..].char_indices() {
+                let count = digit.to_digit(10).ok_or_else(|| {
+                    Error::parse(format!("invalid neighbour count `{}`", digit))
+                        .at(rule, &part[1 + index..])
+                })?;
+                mask |= 1 << count;
+            }
+            *counts = Some(mask);
+        }
+        match (birth, survival) {
+            (Some(birth), Some(survival)) => Ok(Rule { birth, survival }),
+            _ => Err(Error::parse(format!(
+                "expected both birth and survival counts, as in `B3/S23`, found `{}`",
+                rule
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let counts = |mask: u32| -> String {
+            (0..32)
+                .filter(|count| mask & (1 << count) != 0)
+                .map(|count| count.to_string())
+                .collect()
+        };
+        write!(f, "B{}/S{}", counts(self.birth), counts(self.survival))
+    }
+}
+
+/// A set of cells evolving one generation at a time.
+pub trait Universe {
+    /// Moves to the next generation, returning whether any cell changed.
+    fn step(&mut self, rule: &Rule) -> bool;
+
+    /// The number of live cells.
+    fn population(&self) -> usize;
+
+    /// A flat picture of the cells, where `None` marks a hole.
+    fn picture(&self) -> Grid<Option<bool>>;
+
+    fn run(&mut self, rule: &Rule, generations: usize) {
+        for _ in 0..generations {
+            self.step(rule);
+        }
+    }
+
+    /// Moves on until no cell changes, returning the number of generations
+    /// that changed something. Returns `None` when cells still change after
+    /// `limit` generations.
+    fn settle(&mut self, rule: &Rule, limit: usize) -> Option<usize> {
+        (0..=limit).find(|_| !self.step(rule))
+    }
+}
+
+/// The shape of an unbounded space: which cells neighbour each other.
+pub trait Topology {
+    type Cell: Copy + Eq + Hash;
+
+    fn neighbors(&self, cell: Self::Cell) -> Vec<Self::Cell>;
+
+    /// Where the cell is drawn as a `(row, column)` pair, if it is drawn.
+    fn project(&self, cell: Self::Cell) -> Option<(isize, isize)>;
+}
+
+/// The square grid, with `(row, column)` cells.
+#[derive(Clone, Copy, Debug)]
+pub struct Square {
+    /// Steps to the neighbours, such as `grid::ADJACENT`
+    pub steps: &'static [(isize, isize)],
+}
+
+impl Topology for Square {
+    type Cell = (isize, isize);
+
+    fn neighbors(&self, (row, col): Self::Cell) -> Vec<Self::Cell> {
+        self.steps
+            .iter()
+            .map(|step| (row + step.0, col + step.1))
+            .collect()
+    }
+
+    fn project(&self, cell: Self::Cell) -> Option<(isize, isize)> {
+        Some(cell)
+    }
+}
+
+/// The hexagonal grid, in axial coordinates.
+#[derive(Clone, Copy, Debug)]
+pub struct Hex;
+
+const HEX_STEPS: [(i32, i32); 6] = [(1, 0), (1, -1), (0, -1), (-1, 0), (-1, 1), (0, 1)];
+
+impl Topology for Hex {
+    type Cell = (i32, i32);
+
+    fn neighbors(&self, (q, r): Self::Cell) -> Vec<Self::Cell> {
+        HEX_STEPS
+            .iter()
+            .map(|step| (q + step.0, r + step.1))
+            .collect()
+    }
+
+    /// Rows are shifted by half a cell from each other, so a cell spans two
+    /// columns.
+    fn project(&self, (q, r): Self::Cell) -> Option<(isize, isize)> {
+        Some((-r as isize, (2 * q + r) as isize))
+    }
+}
+
+/// The lattice of `N` dimensions, where cells touch when no coordinate
+/// differs by more than 1.
+#[derive(Clone, Debug)]
+pub struct Lattice<const N: usize> {
+    steps: Vec<[i32; N]>,
+}
+
+impl<const N: usize> Lattice<N> {
+    pub fn new() -> Self {
+        let mut steps = vec![[0; N]];
+        for axis in 0..N {
+            steps = steps
+                .into_iter()
+                .flat_map(|step| {
+                    (-1..=1).map(move |delta| {
+                        let mut step = step;
+                        step[axis] = delta;
+                        step
+                    })
+                })
+                .collect();
+        }
+        steps.retain(|step| step.iter().any(|&delta| delta != 0));
+        Lattice { steps }
+    }
+}
+
+impl<const N: usize> Default for Lattice<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> Topology for Lattice<N> {
+    type Cell = [i32; N];
+
+    fn neighbors(&self, cell: Self::Cell) -> Vec<Self::Cell> {
+        self.steps
+            .iter()
+            .map(|step| {
+                let mut neighbor = cell;
+                for (coordinate, delta) in neighbor.iter_mut().zip(step) {
+                    *coordinate += delta;
+                }
+                neighbor
+            })
+            .collect()
+    }
+
+    /// Draws the slice where every coordinate but the first two is 0.
+    fn project(&self, cell: Self::Cell) -> Option<(isize, isize)> {
+        match &cell[..] {
+            [x, y, rest @ ..] if rest.iter().all(|&coordinate| coordinate == 0) => {
+                Some((*y as isize, *x as isize))
+            }
+            [x] => Some((0, *x as isize)),
+            _ => None,
+        }
+    }
+}
+
+/// An unbounded universe, storing its live cells only. Since it cannot hold
+/// infinitely many cells, a cell without live neighbours never comes to life.
+#[derive(Clone, Debug)]
+pub struct Sparse<T: Topology> {
+    topology: T,
+    alive: HashSet<T::Cell>,
+}
+
+impl<T: Topology> Sparse<T> {
+    pub fn new<I: IntoIterator<Item = T::Cell>>(topology: T, alive: I) -> Self {
+        Sparse {
+            topology,
+            alive: alive.into_iter().collect(),
+        }
+    }
+
+    pub fn alive(&self) -> &HashSet<T::Cell> {
+        &self.alive
+    }
+}
+
+impl<T: Topology> Universe for Sparse<T> {
+    fn step(&mut self, rule: &Rule) -> bool {
+        let mut neighbors: HashMap<T::Cell, usize> = HashMap::new();
+        for &cell in &self.alive {
+            for neighbor in self.topology.neighbors(cell) {
+                *neighbors.entry(neighbor).or_insert(0) += 1;
+            }
+        }
+        let mut alive: HashSet<T::Cell> = neighbors
+            .iter()
+            .filter(|(cell, &count)| rule.next(self.alive.contains(cell), count))
+            .map(|(&cell, _)| cell)
+            .collect();
+        if rule.next(true, 0) {
+            // Isolated cells are not counted above
+            alive.extend(
+                self.alive
+                    .iter()
+                    .filter(|cell| !neighbors.contains_key(cell)),
+            );
+        }
+        let changed = alive != self.alive;
+        self.alive = alive;
+        changed
+    }
+
+    fn population(&self) -> usize {
+        self.alive.len()
+    }
+
+    /// Frames the live cells, which moves the picture as they spread.
+    fn picture(&self) -> Grid<Option<bool>> {
+        let drawn: Vec<(isize, isize)> = self
+            .alive
+            .iter()
+            .filter_map(|&cell| self.topology.project(cell))
+            .collect();
+        let top = drawn.iter().map(|cell| cell.0).min().unwrap_or(0);
+        let left = drawn.iter().map(|cell| cell.1).min().unwrap_or(0);
+        let height = drawn.iter().map(|cell| cell.0 - top + 1).max().unwrap_or(0);
+        let width = drawn
+            .iter()
+            .map(|cell| cell.1 - left + 1)
+            .max()
+            .unwrap_or(0);
+        let mut picture = Grid::new(width as usize, height as usize, Some(false));
+        for (row, col) in drawn {
+            picture[((row - top) as usize, (col - left) as usize)] = Some(true);
+        }
+        picture
+    }
+}
+
+/// How cells of a bounded universe see each other.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Neighborhood {
+    /// The cells one step away
+    Adjacent,
+    /// The first cell met going by each step, skipping holes
+    LineOfSight,
+}
+
+/// A universe limited to a grid, whose `None` positions are holes holding no
+/// cell.
+#[derive(Clone, Debug)]
+pub struct Bounded {
+    cells: Grid<Option<bool>>,
+    neighbors: Grid<Vec<Position>>,
+}
+
+impl Bounded {
+    pub fn new(
+        cells: Grid<Option<bool>>,
+        steps: &[(isize, isize)],
+        neighborhood: Neighborhood,
+    ) -> Self {
+        let neighbors = Grid::from_fn(cells.width(), cells.height(), |position| {
+            steps
+                .iter()
+                .filter_map(|&step| match neighborhood {
+                    Neighborhood::Adjacent => cells
+                        .offset(position, step)
+                        .filter(|&neighbor| cells[neighbor].is_some()),
+                    Neighborhood::LineOfSight => cells
+                        .ray(position, step)
+                        .find(|&neighbor| cells[neighbor].is_some()),
+                })
+                .collect()
+        });
+        Bounded { cells, neighbors }
+    }
+
+    pub fn cells(&self) -> &Grid<Option<bool>> {
+        &self.cells
+    }
+}
+
+impl Universe for Bounded {
+    fn step(&mut self, rule: &Rule) -> bool {
+        let cells = &self.cells;
+        let neighbors = &self.neighbors;
+        let next = Grid::from_fn(cells.width(), cells.height(), |position| {
+            cells[position].map(|alive| {
+                let count = neighbors[position]
+                    .iter()
+                    .filter(|&&neighbor| cells[neighbor] == Some(true))
+                    .count();
+                rule.next(alive, count)
+            })
+        });
+        let changed = next != self.cells;
+        self.cells = next;
+        changed
+    }
+
+    fn population(&self) -> usize {
+        self.cells
+            .iter()
+            .filter(|&&cell| cell == Some(true))
+            .count()
+    }
+
+    fn picture(&self) -> Grid<Option<bool>> {
+        self.cells.clone()
+    }
+}
+
+/// The automaton of a puzzle.
+pub struct Automaton {
+    pub universe: Box<dyn Universe>,
+    pub rule: Rule,
+    /// Generations run by the puzzle, `None` when it runs until nothing
+    /// changes
+    pub generations: Option<usize>,
+}
+
+/// Reads the input of one of `DAYS` as the automaton of `part` of its
+/// puzzle.
+///
+/// # Panics
+///
+/// Panics when the day is not one of `DAYS`.
+pub fn automaton(day: usize, part: u8, input: &str) -> Result<Automaton> {
+    let (universe, rule, generations): (Box<dyn Universe>, _, _) = match (day, part) {
+        (11, 1) => (
+            Box::new(day11::waiting_area(input, Neighborhood::Adjacent)?),
+            day11::ADJACENT_RULE,
+            None,
+        ),
+        (11, _) => (
+            Box::new(day11::waiting_area(input, Neighborhood::LineOfSight)?),
+            day11::LINE_OF_SIGHT_RULE,
+            None,
+        ),
+        (17, 1) => (
+            Box::new(day17::pocket_dimension::<3>(input)?),
+            Rule::LIFE,
+            Some(day17::CYCLES),
+        ),
+        (17, _) => (
+            Box::new(day17::pocket_dimension::<4>(input)?),
+            Rule::LIFE,
+            Some(day17::CYCLES),
+        ),
+        (24, _) => (
+            Box::new(day24::lobby_floor(input)?),
+            day24::RULE,
+            Some(day24::DAYS),
+        ),
+        _ => panic!("day {} is not a cellular automaton", day),
+    };
+    Ok(Automaton {
+        universe,
+        rule,
+        generations,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::ADJACENT;
+
+    #[test]
+    fn test_rule() {
+        let rule: Rule = "B36/S23".parse().unwrap();
+        assert!(rule.next(false, 6));
+        assert!(!rule.next(true, 6));
+        assert_eq!(rule.to_string(), "B36/S23");
+        assert_eq!("s23/b3".parse::<Rule>().unwrap(), Rule::LIFE);
+        assert_eq!("B/S".parse::<Rule>().unwrap().to_string(), "B/S");
+        assert!("B3".parse::<Rule>().is_err());
+        assert!("B3/S2x".parse::<Rule>().is_err());
+        assert!("B3/B2".parse::<Rule>().is_err());
+    }
+
+    #[test]
+    fn test_sparse() {
+        // A blinker turns around every generation
+        let mut blinker = Sparse::new(Square { steps: &ADJACENT }, vec![(0, -1), (0, 0), (0, 1)]);
+        blinker.step(&Rule::LIFE);
+        assert!(blinker.alive().contains(&(-1, 0)));
+        assert_eq!(blinker.settle(&Rule::LIFE, 10), None);
+        assert_eq!(blinker.population(), 3);
+
+        let mut cube = Sparse::new(Lattice::<3>::new(), vec![[0, 0, 0]]);
+        assert_eq!(Lattice::<3>::new().neighbors([0, 0, 0]).len(), 26);
+        cube.run(&Rule::new(&[1], &[]), 1);
+        assert_eq!(cube.population(), 26);
+        assert_eq!(Hex.neighbors((0, 0)).len(), 6);
+        let picture = cube.picture();
+        assert_eq!((picture.width(), picture.height()), (3, 3));
+        assert_eq!(picture[(1, 1)], Some(false));
+    }
+
+    #[test]
+    fn test_bounded() {
+        let cells = Grid::from_rows(vec![
+            vec![Some(false), None, Some(false)],
+            vec![Some(true), Some(true), Some(true)],
+        ])
+        .unwrap();
+        let universe = Bounded::new(cells.clone(), &ADJACENT, Neighborhood::Adjacent);
+        assert_eq!(universe.neighbors[(0, 0)], vec![(1, 0), (1, 1)]);
+        let mut universe = Bounded::new(cells, &ADJACENT, Neighborhood::LineOfSight);
+        assert_eq!(universe.neighbors[(0, 0)], vec![(0, 2), (1, 0), (1, 1)]);
+        assert_eq!(
+            universe.settle(&Rule::new(&[0], &[0, 1, 2, 3]), 10),
+            Some(0)
+        );
+        assert_eq!(universe.population(), 3);
+    }
+}
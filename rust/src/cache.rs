@@ -0,0 +1,163 @@
+use std::env;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use crate::error::Result;
+use crate::solution::Answer;
+
+/// A hash of the sources of the solvers, computed by `build.rs`: answers
+/// cached before a solver changed are never read.
+pub const SOLVER_VERSION: &str = env!("AOC2020_SOLVER_HASH");
+
+const EXTENSION: &str = "answer";
+
+/// The 64-bit FNV-1a hash of `bytes`.
+pub fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+/// Answers saved on disk, one file per day, part, input and solver version.
+/// Only successful answers are cached, errors are always computed again.
+pub struct Cache {
+    dir: PathBuf,
+    version: String,
+}
+
+impl Cache {
+    /// A cache of the answers of the current solvers.
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Self {
+        Cache::with_version(dir, SOLVER_VERSION)
+    }
+
+    /// A cache of the answers of the solvers of `version`.
+    pub fn with_version<P: Into<PathBuf>>(dir: P, version: &str) -> Self {
+        Cache {
+            dir: dir.into(),
+            version: version.to_string(),
+        }
+    }
+
+    /// `$XDG_CACHE_HOME/aoc2020`, falling back on `~/.cache/aoc2020`, then on
+    /// the temporary directory.
+    pub fn default_dir() -> PathBuf {
+        match (env::var_os("XDG_CACHE_HOME"), env::var_os("HOME")) {
+            (Some(cache), _) if !cache.is_empty() => PathBuf::from(cache).join("aoc2020"),
+            (_, Some(home)) if !home.is_empty() => PathBuf::from(home).join(".cache/aoc2020"),
+            _ => env::temp_dir().join("aoc2020-cache"),
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn path(&self, day: usize, part: u8, input: &str) -> PathBuf {
+        self.dir.join(format!(
+            "day{}-part{}-{:016x}-{}.{}",
+            day,
+            part,
+            fnv1a(input.as_bytes()),
+            self.version,
+            EXTENSION
+        ))
+    }
+
+    /// The cached answer of a part, if any. An unreadable entry is a miss.
+    pub fn get(&self, day: usize, part: u8, input: &str) -> Option<Answer> {
+        let content = fs::read_to_string(self.path(day, part, input)).ok()?;
+        decode(&content)
+    }
+
+    pub fn insert(&self, day: usize, part: u8, input: &str, answer: &Answer) -> Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        fs::write(self.path(day, part, input), encode(answer))?;
+        Ok(())
+    }
+
+    /// Removes every cached answer, returning how many there were.
+    pub fn clear(&self) -> Result<usize> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(0),
+            Err(error) => return Err(error.into()),
+        };
+        let mut removed = 0;
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension() == Some(EXTENSION.as_ref()) {
+                fs::remove_file(path)?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+}
+
+/// The kind of the answer on the first line, its value on the rest.
+fn encode(answer: &Answer) -> String {
+    let kind = match answer {
+        Answer::Integer(_) => "integer",
+        Answer::BigInteger(_) => "big_integer",
+        Answer::String(_) => "string",
+        Answer::None => "none",
+    };
+    format!("{}\n{}", kind, answer)
+}
+
+fn decode(content: &str) -> Option<Answer> {
+    let (kind, value) = content.split_once('\n')?;
+    match kind {
+        "integer" => value.parse().ok().map(Answer::Integer),
+        "big_integer" => value.parse().ok().map(Answer::BigInteger),
+        "string" => Some(Answer::String(value.to_string())),
+        "none" if value.is_empty() => Some(Answer::None),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process;
+
+    #[test]
+    fn test_fnv1a() {
+        assert_eq!(fnv1a(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a(b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(fnv1a(b"foobar"), 0x8594_4171_f739_67e8);
+    }
+
+    #[test]
+    fn test_cache() {
+        let dir = env::temp_dir().join(format!("aoc2020-cache-test-{}", process::id()));
+        let cache = Cache::new(&dir);
+        assert_eq!(cache.clear().unwrap(), 0);
+
+        let answers = [
+            Answer::Integer(-12),
+            Answer::BigInteger(i128::MAX),
+            Answer::from("mxmxvkd,sqjhc\nfvjkl"),
+            Answer::None,
+        ];
+        for (day, answer) in answers.iter().enumerate() {
+            assert_eq!(cache.get(day + 1, 1, "input"), None);
+            cache.insert(day + 1, 1, "input", answer).unwrap();
+            assert_eq!(cache.get(day + 1, 1, "input").as_ref(), Some(answer));
+            assert_eq!(cache.get(day + 1, 2, "input"), None);
+            assert_eq!(cache.get(day + 1, 1, "other input"), None);
+        }
+        // Answers cached by other solvers are left out, but still cleared
+        let old = Cache::with_version(&dir, "old");
+        assert_eq!(old.get(1, 1, "input"), None);
+        old.insert(1, 1, "input", &Answer::Integer(7)).unwrap();
+        assert_eq!(cache.get(1, 1, "input").as_ref(), Some(&answers[0]));
+        fs::write(dir.join("notes.txt"), "kept").unwrap();
+        assert_eq!(cache.clear().unwrap(), 5);
+        assert_eq!(cache.get(1, 1, "input"), None);
+        assert!(dir.join("notes.txt").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
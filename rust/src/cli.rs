@@ -0,0 +1,663 @@
+use std::path::PathBuf;
+use std::thread;
+use std::time::Duration;
+
+use aoc2020::automaton::{self, Rule};
+use aoc2020::day2;
+use aoc2020::output::Format;
+use aoc2020::visualize;
+
+pub const USAGE: &str =
+    "Usage: rust [run | bench | verify | matrix | life | report | generate | cache clear] [--all] [--day <days>] [--part <1|2>] [--input <file> | --input-dir <dir>]
+
+Commands:
+    run                 Print the answers of the selected days (the default)
+    bench               Time repeated runs of the selected days
+    verify              Compare the answers of the selected days with the expected ones
+    matrix              Run the selected days on the inputs of every user, `<user>/day<N>.txt` in the input directory
+    life                Run the cellular automaton of days 11, 17 and 24 (the default days)
+    report              List every password of day 2 (the default day) with the policies it passes or why it fails them
+    generate            Print a random input of the selected day
+    cache clear         Remove the answers cached by `run` and `matrix`
+
+Options:
+    --all               Run every day (the default when no day is given)
+    --day <days>        Days to run, e.g. `14`, `1-10` or `1,3,20-25`
+    --part <1|2>        Only run the given part of each selected day
+    --input <file>      Input of the selected day, `-` reads it from stdin
+    --input-dir <dir>   Directory containing `day<N>.txt` inputs (default: the repository `inputs`)
+    --time              Also print how long parsing and each part took
+    --mem               Also print the peak and total memory allocated by parsing and each part
+    --parallel          Run the days concurrently, one thread per available core, printing them in order
+    --jobs <n>          Run the days concurrently on `n` threads
+    --format <format>   Output of `run`: `text` (default), `json` (one object per line) or `csv`; of `report`: `text` (a table) or `json`
+    --runs <n>          Number of runs of `bench` (default: 10)
+    --answers <file>    Expected answers of `verify` (default: `answers.toml` in the input directory)
+    --rule <rule>       Life-like rule of `life`, e.g. `B3/S23` (default: the rule of the puzzle)
+    --policy <rule>     Password policy `report` checks instead of those of the input, e.g. `all(length(8-), not(forbid(abc)))`
+    --generations <n>   Generations run by `life` (default: those of the puzzle)
+    --seed <n>          Seed of the input of `generate` (default: taken from the clock)
+    --no-cache          Solve the days again rather than reading their cached answers (`verify` always does)
+    --visualize         Watch days 11, 17, 20 and 24 (the default days) being solved in the terminal
+    --frames <dir>      Save the frames of `--visualize` as PPM images in `dir` instead
+    --delay <ms>        Time each frame of `--visualize` is shown (default: 100)
+    --help              Print this message";
+
+const NUM_OF_DAYS: usize = 25;
+const DEFAULT_INPUT_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../inputs");
+const DEFAULT_RUNS: usize = 10;
+const DEFAULT_DELAY: Duration = Duration::from_millis(100);
+
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Run,
+    Bench {
+        runs: usize,
+    },
+    /// Check the answers against the given `answers.toml`
+    Verify {
+        answers: PathBuf,
+    },
+    /// Run the input of an automaton day under `rule`, or the rule of the
+    /// puzzle
+    Life {
+        rule: Option<Rule>,
+        generations: Option<usize>,
+    },
+    /// Show every step of solving the days, or save them in `frames`
+    Visualize {
+        frames: Option<PathBuf>,
+        delay: Duration,
+    },
+    /// List the policies every password of day 2 passes or fails, or only
+    /// `policy`
+    Report {
+        policy: Option<day2::Rule>,
+    },
+    /// Print a random input of a day
+    Generate {
+        seed: Option<u64>,
+    },
+    /// Run the days on the inputs of every user, see `workspace::users`
+    Matrix,
+    /// Remove every cached answer
+    ClearCache,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Input {
+    /// A directory holding one `day<N>.txt` file per day
+    Dir(PathBuf),
+    /// A single input file, only valid when one day is selected
+    File(PathBuf),
+    Stdin,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Options {
+    pub command: Command,
+    pub days: Vec<usize>,
+    pub part: Option<u8>,
+    pub input: Input,
+    pub time: bool,
+    pub format: Format,
+    /// Number of days run at the same time
+    pub jobs: usize,
+    /// Whether `run` and `matrix` read and save the answers in the cache
+    pub cache: bool,
+    /// Whether `run` prints the memory used by each day
+    pub mem: bool,
+}
+
+impl Options {
+    pub fn parse<I: Iterator<Item = String>>(mut args: I) -> Result<Options, String> {
+        let mut days = None;
+        let mut part = None;
+        let mut all = false;
+        let mut input = None;
+        let mut input_dir = None;
+        let mut command = String::from("run");
+        let mut runs = None;
+        let mut answers = None;
+        let mut time = false;
+        let mut jobs = None;
+        let mut format = Format::Text;
+        let mut rule = None;
+        let mut policy = None;
+        let mut generations = None;
+        let mut visualize = false;
+        let mut frames = None;
+        let mut delay = None;
+        let mut seed = None;
+        let mut cache = true;
+        let mut mem = false;
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "run" | "bench" | "verify" | "matrix" | "life" | "report" | "generate" => {
+                    command = arg
+                }
+                "cache" => match args.next().as_deref() {
+                    Some("clear") => command = "cache clear".to_string(),
+                    _ => return Err("`cache` expects `clear`".to_string()),
+                },
+                "--no-cache" => cache = false,
+                "--time" => time = true,
+                "--mem" => mem = true,
+                "--parallel" => {
+                    jobs = Some(thread::available_parallelism().map_or(1, |jobs| jobs.get()))
+                }
+                "--jobs" | "-j" => {
+                    let value = args.next().ok_or("`--jobs` expects a value")?;
+                    jobs = Some(parse_count("jobs", &value)?);
+                }
+                "--format" => {
+                    let value = args.next().ok_or("`--format` expects a value")?;
+                    format = parse_format(&value)?;
+                }
+                "--answers" => {
+                    let value = args.next().ok_or("`--answers` expects a file")?;
+                    answers = Some(PathBuf::from(value));
+                }
+                "--runs" => {
+                    let value = args.next().ok_or("`--runs` expects a value")?;
+                    runs = Some(parse_count("runs", &value)?);
+                }
+                "--rule" => {
+                    let value = args.next().ok_or("`--rule` expects a value")?;
+                    let parsed = value
+                        .parse()
+                        .map_err(|error| format!("Invalid rule `{}`: {}", value, error))?;
+                    rule = Some(parsed);
+                }
+                "--policy" => {
+                    let value = args.next().ok_or("`--policy` expects a rule")?;
+                    let parsed = value
+                        .parse()
+                        .map_err(|error| format!("Invalid policy `{}`: {}", value, error))?;
+                    policy = Some(parsed);
+                }
+                "--generations" => {
+                    let value = args.next().ok_or("`--generations` expects a value")?;
+                    generations = Some(parse_count("generations", &value)?);
+                }
+                "--seed" => {
+                    let value = args.next().ok_or("`--seed` expects a value")?;
+                    let parsed = value
+                        .parse()
+                        .map_err(|_| format!("`{}` is not a seed, expected a number", value))?;
+                    seed = Some(parsed);
+                }
+                "--visualize" => visualize = true,
+                "--frames" => {
+                    let value = args.next().ok_or("`--frames` expects a directory")?;
+                    frames = Some(PathBuf::from(value));
+                }
+                "--delay" => {
+                    let value = args.next().ok_or("`--delay` expects a value")?;
+                    let delay_ms = value
+                        .parse()
+                        .map_err(|_| format!("`{}` is not a number of milliseconds", value))?;
+                    delay = Some(Duration::from_millis(delay_ms));
+                }
+                "--all" => all = true,
+                "--day" | "-d" => {
+                    let value = args.next().ok_or("`--day` expects a value")?;
+                    days = Some(parse_days(&value)?);
+                }
+                "--part" | "-p" => {
+                    let value = args.next().ok_or("`--part` expects a value")?;
+                    part = Some(parse_part(&value)?);
+                }
+                "--input" | "-i" => {
+                    let value = args.next().ok_or("`--input` expects a file")?;
+                    input = Some(if value == "-" {
+                        Input::Stdin
+                    } else {
+                        Input::File(PathBuf::from(value))
+                    });
+                }
+                "--input-dir" => {
+                    let value = args.next().ok_or("`--input-dir` expects a directory")?;
+                    input_dir = Some(Input::Dir(PathBuf::from(value)));
+                }
+                _ => return Err(format!("Unknown argument `{}`", arg)),
+            }
+        }
+
+        if visualize {
+            if command != "run" {
+                return Err(format!(
+                    "`--visualize` cannot be combined with `{}`",
+                    command
+                ));
+            }
+            command = "visualize".to_string();
+        } else if frames.is_some() || delay.is_some() {
+            return Err("`--frames` and `--delay` are only valid with `--visualize`".to_string());
+        }
+        if all && days.is_some() {
+            return Err("`--all` cannot be combined with `--day`".to_string());
+        }
+        if runs.is_some() && command != "bench" {
+            return Err("`--runs` is only valid with `bench`".to_string());
+        }
+        if answers.is_some() && command != "verify" {
+            return Err("`--answers` is only valid with `verify`".to_string());
+        }
+        if (rule.is_some() || generations.is_some()) && command != "life" {
+            return Err("`--rule` and `--generations` are only valid with `life`".to_string());
+        }
+        if policy.is_some() && command != "report" {
+            return Err("`--policy` is only valid with `report`".to_string());
+        }
+        if policy.is_some() && part.is_some() {
+            return Err("`--policy` cannot be combined with `--part`".to_string());
+        }
+        if seed.is_some() && command != "generate" {
+            return Err("`--seed` is only valid with `generate`".to_string());
+        }
+        let runs_days = ["run", "verify", "matrix"].contains(&command.as_str());
+        if !cache && !runs_days {
+            return Err(format!("`{}` does not use the cache", command));
+        }
+        if command == "cache clear"
+            && (days.is_some() || part.is_some() || input.is_some() || input_dir.is_some())
+        {
+            return Err("`cache clear` removes the answers of every day and input".to_string());
+        }
+        if mem && command != "run" {
+            return Err("`--mem` is only valid with `run`".to_string());
+        }
+        if mem && jobs.is_some() {
+            return Err("`--mem` cannot measure days run in parallel".to_string());
+        }
+        if jobs.is_some() && !runs_days {
+            return Err(format!("`{}` cannot run days in parallel", command));
+        }
+        // Days run in parallel only tell how long they took through their timings
+        let time = time || jobs.is_some();
+        let jobs = jobs.unwrap_or(1);
+        if format == Format::Csv && command == "report" {
+            return Err("`report` supports the text and json formats".to_string());
+        }
+        if format != Format::Text && command != "run" && command != "report" {
+            return Err(format!("`{}` only supports the text format", command));
+        }
+        if mem && format != Format::Text {
+            return Err("`--mem` only supports the text format".to_string());
+        }
+        // Cached answers take no memory to find, and would only check the
+        // solvers against themselves
+        let cache = cache && !mem && command != "verify";
+
+        // Some commands only know a few days
+        let supported: Option<&[usize]> = match command.as_str() {
+            "life" => Some(&automaton::DAYS),
+            "visualize" => Some(&visualize::DAYS),
+            "report" => Some(&[2]),
+            _ => None,
+        };
+        let days = match (days, supported) {
+            (Some(days), Some(supported)) => {
+                if let Some(day) = days.iter().find(|day| !supported.contains(day)) {
+                    return Err(format!("`{}` does not support day {}", command, day));
+                }
+                days
+            }
+            (Some(days), None) => days,
+            (None, Some(supported)) => supported.to_vec(),
+            (None, None) => (1..=NUM_OF_DAYS).collect(),
+        };
+        if command == "matrix" && input.is_some() {
+            return Err("`matrix` reads the inputs of every user in `--input-dir`".to_string());
+        }
+        if command == "generate" {
+            if days.len() != 1 {
+                return Err("`generate` requires exactly one day to be selected".to_string());
+            }
+            if input.is_some() || input_dir.is_some() {
+                return Err("`generate` does not read any input".to_string());
+            }
+        }
+        let input = match (input, input_dir) {
+            (Some(_), Some(_)) => {
+                return Err("`--input` cannot be combined with `--input-dir`".to_string())
+            }
+            (Some(_), None) if days.len() != 1 => {
+                return Err("`--input` requires exactly one day to be selected".to_string())
+            }
+            (Some(input), None) | (None, Some(input)) => input,
+            (None, None) => Input::Dir(PathBuf::from(DEFAULT_INPUT_DIR)),
+        };
+
+        let command = match command.as_str() {
+            "bench" => Command::Bench {
+                runs: runs.unwrap_or(DEFAULT_RUNS),
+            },
+            "verify" => Command::Verify {
+                answers: answers.unwrap_or_else(|| match &input {
+                    Input::Dir(dir) => dir.join("answers.toml"),
+                    _ => PathBuf::from(DEFAULT_INPUT_DIR).join("answers.toml"),
+                }),
+            },
+            "life" => Command::Life { rule, generations },
+            "generate" => Command::Generate { seed },
+            "report" => Command::Report { policy },
+            "matrix" => Command::Matrix,
+            "cache clear" => Command::ClearCache,
+            "visualize" => Command::Visualize {
+                frames,
+                delay: delay.unwrap_or(DEFAULT_DELAY),
+            },
+            _ => Command::Run,
+        };
+
+        Ok(Options {
+            command,
+            days,
+            part,
+            input,
+            time,
+            format,
+            jobs,
+            cache,
+            mem,
+        })
+    }
+}
+
+fn parse_day(day: &str) -> Result<usize, String> {
+    match day.trim().parse() {
+        Ok(day) if (1..=NUM_OF_DAYS).contains(&day) => Ok(day),
+        _ => Err(format!(
+            "`{}` is not a day between 1 and {}",
+            day, NUM_OF_DAYS
+        )),
+    }
+}
+
+fn parse_days(spec: &str) -> Result<Vec<usize>, String> {
+    let mut days = vec![];
+    for item in spec.split(',') {
+        match item.find('-') {
+            Some(index) => {
+                let first = parse_day(&item[..index])?;
+                let last = parse_day(&item[index + 1..])?;
+                if first > last {
+                    return Err(format!("`{}` is an empty range of days", item));
+                }
+                days.extend(first..=last);
+            }
+            None => days.push(parse_day(item)?),
+        }
+    }
+    days.sort_unstable();
+    days.dedup();
+    Ok(days)
+}
+
+fn parse_part(part: &str) -> Result<u8, String> {
+    match part {
+        "1" => Ok(1),
+        "2" => Ok(2),
+        _ => Err(format!("`{}` is not a part, expected 1 or 2", part)),
+    }
+}
+
+fn parse_format(format: &str) -> Result<Format, String> {
+    match format {
+        "text" => Ok(Format::Text),
+        "json" => Ok(Format::Json),
+        "csv" => Ok(Format::Csv),
+        _ => Err(format!(
+            "`{}` is not a format, expected text, json or csv",
+            format
+        )),
+    }
+}
+
+fn parse_count(name: &str, count: &str) -> Result<usize, String> {
+    match count.parse() {
+        Ok(count) if count > 0 => Ok(count),
+        _ => Err(format!("`{}` is not a positive number of {}", count, name)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Options, String> {
+        Options::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn test_parse_days() {
+        assert_eq!(parse_days("14"), Ok(vec![14]));
+        assert_eq!(parse_days("1-3"), Ok(vec![1, 2, 3]));
+        assert_eq!(parse_days("20-22,1,3,21"), Ok(vec![1, 3, 20, 21, 22]));
+        assert!(parse_days("0").is_err());
+        assert!(parse_days("26").is_err());
+        assert!(parse_days("5-3").is_err());
+        assert!(parse_days("a").is_err());
+    }
+
+    #[test]
+    fn test_parse_options() {
+        assert_eq!(
+            parse(&["run", "--day", "14", "--part", "2"]),
+            Ok(Options {
+                command: Command::Run,
+                days: vec![14],
+                part: Some(2),
+                input: Input::Dir(PathBuf::from(DEFAULT_INPUT_DIR)),
+                time: false,
+                format: Format::Text,
+                jobs: 1,
+                cache: true,
+                mem: false,
+            })
+        );
+        assert_eq!(parse(&[]).unwrap().days, (1..=25).collect::<Vec<_>>());
+        assert_eq!(parse(&["--all"]).unwrap().days.len(), 25);
+        assert!(parse(&["--all", "--day", "1"]).is_err());
+        assert!(parse(&["--part", "3"]).is_err());
+        assert!(parse(&["--day"]).is_err());
+        assert!(parse(&["--verbose"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_bench() {
+        assert_eq!(
+            parse(&["bench", "--day", "15"]).unwrap().command,
+            Command::Bench { runs: DEFAULT_RUNS }
+        );
+        assert_eq!(
+            parse(&["bench", "--runs", "3"]).unwrap().command,
+            Command::Bench { runs: 3 }
+        );
+        assert!(parse(&["--time"]).unwrap().time);
+        assert!(parse(&["bench", "--runs", "0"]).is_err());
+        assert!(parse(&["run", "--runs", "3"]).is_err());
+        assert!(parse(&["bench", "--format", "json"]).is_err());
+        assert!(parse(&["bench", "--jobs", "2"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_jobs() {
+        assert_eq!(parse(&["--jobs", "4"]).unwrap().jobs, 4);
+        assert!(parse(&["--jobs", "4"]).unwrap().time);
+        assert!(parse(&["--parallel"]).unwrap().jobs >= 1);
+        assert!(parse(&["--jobs", "0"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_verify() {
+        assert_eq!(
+            parse(&["verify", "--input-dir", "/tmp/inputs"])
+                .unwrap()
+                .command,
+            Command::Verify {
+                answers: PathBuf::from("/tmp/inputs/answers.toml")
+            }
+        );
+        assert_eq!(
+            parse(&[
+                "verify",
+                "--day",
+                "1",
+                "--input",
+                "-",
+                "--answers",
+                "a.toml"
+            ])
+            .unwrap()
+            .command,
+            Command::Verify {
+                answers: PathBuf::from("a.toml")
+            }
+        );
+        assert!(parse(&["--answers", "a.toml"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_life() {
+        let options = parse(&["life", "--rule", "B36/S23", "--generations", "10"]).unwrap();
+        assert_eq!(options.days, vec![11, 17, 24]);
+        assert_eq!(
+            options.command,
+            Command::Life {
+                rule: Some(Rule::new(&[3, 6], &[2, 3])),
+                generations: Some(10)
+            }
+        );
+        assert_eq!(parse(&["life", "-d", "17"]).unwrap().days, vec![17]);
+        assert!(parse(&["life", "-d", "1"]).is_err());
+        assert!(parse(&["life", "--rule", "B9"]).is_err());
+        assert_eq!(parse(&["life", "--part", "2"]).unwrap().part, Some(2));
+        assert!(parse(&["life", "--jobs", "2"]).is_err());
+        assert!(parse(&["--rule", "B3/S23"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_report() {
+        let options = parse(&["report", "--format", "json", "--part", "1"]).unwrap();
+        assert_eq!(options.command, Command::Report { policy: None });
+        assert_eq!(options.days, vec![2]);
+        assert_eq!(options.format, Format::Json);
+        assert_eq!(options.part, Some(1));
+        assert!(parse(&["report", "-d", "3"]).is_err());
+        assert!(parse(&["report", "--format", "csv"]).is_err());
+        assert!(parse(&["report", "--jobs", "2"]).is_err());
+        assert!(parse(&["report", "--no-cache"]).is_err());
+        let options = parse(&["report", "--policy", "length(8-)"]).unwrap();
+        assert_eq!(
+            options.command,
+            Command::Report {
+                policy: Some("length(8-)".parse().unwrap())
+            }
+        );
+        assert!(parse(&["report", "--policy", "length(x)"]).is_err());
+        assert!(parse(&["report", "--policy", "length(8-)", "--part", "1"]).is_err());
+        assert!(parse(&["--policy", "length(8-)"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_visualize() {
+        let options = parse(&["--visualize", "--day", "20", "--frames", "frames"]).unwrap();
+        assert_eq!(options.days, vec![20]);
+        assert_eq!(
+            options.command,
+            Command::Visualize {
+                frames: Some(PathBuf::from("frames")),
+                delay: DEFAULT_DELAY
+            }
+        );
+        assert_eq!(parse(&["--visualize"]).unwrap().days, vec![11, 17, 20, 24]);
+        assert!(parse(&["--visualize", "-d", "5"]).is_err());
+        assert!(parse(&["bench", "--visualize"]).is_err());
+        assert!(parse(&["--delay", "50"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_generate() {
+        let options = parse(&["generate", "--day", "7", "--seed", "42"]).unwrap();
+        assert_eq!(options.days, vec![7]);
+        assert_eq!(options.command, Command::Generate { seed: Some(42) });
+        assert_eq!(
+            parse(&["generate", "-d", "7"]).unwrap().command,
+            Command::Generate { seed: None }
+        );
+        assert!(parse(&["generate"]).is_err());
+        assert!(parse(&["generate", "-d", "1-2"]).is_err());
+        assert!(parse(&["generate", "-d", "1", "--input", "-"]).is_err());
+        assert!(parse(&["generate", "-d", "1", "--seed", "x"]).is_err());
+        assert!(parse(&["--seed", "1"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_cache() {
+        assert!(parse(&[]).unwrap().cache);
+        assert!(!parse(&["run", "--no-cache"]).unwrap().cache);
+        assert!(!parse(&["verify"]).unwrap().cache);
+        assert!(!parse(&["verify", "--no-cache"]).unwrap().cache);
+        assert!(parse(&["bench", "--no-cache"]).is_err());
+        assert_eq!(
+            parse(&["cache", "clear"]).unwrap().command,
+            Command::ClearCache
+        );
+        assert!(parse(&["cache"]).is_err());
+        assert!(parse(&["cache", "list"]).is_err());
+        assert!(parse(&["cache", "clear", "--day", "15"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_matrix() {
+        let options = parse(&["matrix", "--input-dir", "team", "-d", "1-3", "-j", "2"]).unwrap();
+        assert_eq!(options.command, Command::Matrix);
+        assert_eq!(options.input, Input::Dir(PathBuf::from("team")));
+        assert_eq!(options.jobs, 2);
+        assert!(parse(&["matrix", "-d", "1", "--input", "day1.txt"]).is_err());
+        assert!(parse(&["matrix", "--format", "csv"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_mem() {
+        let options = parse(&["--mem", "-d", "15"]).unwrap();
+        assert!(options.mem);
+        assert!(!options.cache);
+        assert!(!parse(&[]).unwrap().mem);
+        assert!(parse(&["verify", "--mem"]).is_err());
+        assert!(parse(&["--mem", "--parallel"]).is_err());
+        assert!(parse(&["--mem", "--format", "json"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_format() {
+        assert_eq!(parse(&[]).unwrap().format, Format::Text);
+        assert_eq!(parse(&["--format", "csv"]).unwrap().format, Format::Csv);
+        assert_eq!(parse(&["--format", "json"]).unwrap().format, Format::Json);
+        assert!(parse(&["--format", "xml"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_input() {
+        assert_eq!(
+            parse(&["--day", "3", "--input", "my_input.txt"])
+                .unwrap()
+                .input,
+            Input::File(PathBuf::from("my_input.txt"))
+        );
+        assert_eq!(
+            parse(&["--day", "3", "--input", "-"]).unwrap().input,
+            Input::Stdin
+        );
+        assert_eq!(
+            parse(&["--input-dir", "/tmp/inputs"]).unwrap().input,
+            Input::Dir(PathBuf::from("/tmp/inputs"))
+        );
+        assert!(parse(&["--day", "1-2", "--input", "day1.txt"]).is_err());
+        assert!(parse(&["--day", "1", "--input", "a", "--input-dir", "b"]).is_err());
+    }
+}
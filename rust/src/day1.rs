@@ -1,10 +1,15 @@
-use crate::utilities;
-use std::collections::HashSet;
+use crate::error::{Error, Result};
+use crate::generate::Rng;
+use crate::solution::{Answer, Solution};
+use crate::utilities::parse_lines;
+use std::collections::{BTreeMap, HashMap, HashSet};
 
-pub fn two_sum(data: &[i32], target: i32) -> Option<i32> {
+/// Returns the product of two entries of `data` summing to `target`.
+pub fn two_sum(data: &[i32], target: i32) -> Option<i64> {
     let mut set = HashSet::new();
     for &number in data {
-        let other = target - number;
+        let number = i64::from(number);
+        let other = i64::from(target) - number;
         match set.get(&other) {
             None => {
                 set.insert(number);
@@ -17,26 +22,599 @@ pub fn two_sum(data: &[i32], target: i32) -> Option<i32> {
     None
 }
 
-fn part_1(data: &[i32]) {
-    println!("\tPart 1: {}", two_sum(data, 2020).unwrap());
+/// Entries of the expense report summing to a target.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Combination {
+    /// Positions of the entries in the report, in increasing order
+    pub indices: Vec<usize>,
+    /// The entries, in the order of `indices`
+    pub values: Vec<i32>,
 }
 
-fn part_2(data: &[i32]) {
-    let mut result = 0;
-    for (index, &number) in data.iter().enumerate() {
-        if let Some(other) = two_sum(&data[index + 1..], 2020 - number) {
-            result = other * number;
-            break;
+impl Combination {
+    fn new(data: &[i32], mut indices: Vec<usize>) -> Self {
+        indices.sort_unstable();
+        let values = indices.iter().map(|&index| data[index]).collect();
+        Combination { indices, values }
+    }
+
+    /// The product of the entries, `None` when it overflows an `i128`, which
+    /// takes more than 4 entries.
+    pub fn product(&self) -> Option<i128> {
+        self.values.iter().try_fold(1i128, |product, &value| {
+            product.checked_mul(i128::from(value))
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Search {
+    /// Stop at the first combination found
+    First,
+    All,
+}
+
+/// The combinations of `k` entries of `data` summing to `target`, sorted by
+/// their indices. Entries of equal value at different indices make different
+/// combinations.
+///
+/// The entries are sorted and searched with two pointers, taking
+/// `O(n^(k - 1))` time at worst. Looking for the first combination of 4
+/// entries or more, it rather meets in the middle: the sums of every `k / 2`
+/// entries are looked up for the sum of the other entries, in
+/// `O(n^(k - k / 2))` time.
+pub fn k_sum(data: &[i32], target: i32, k: usize, search: Search) -> Vec<Combination> {
+    if k == 0 || k > data.len() {
+        return vec![];
+    }
+    if search == Search::First && k >= 4 {
+        return meet_in_the_middle(data, i64::from(target), k)
+            .map(|indices| Combination::new(data, indices))
+            .into_iter()
+            .collect();
+    }
+
+    let mut order: Vec<usize> = (0..data.len()).collect();
+    order.sort_by_key(|&index| data[index]);
+    let sorted: Vec<i64> = order.iter().map(|&index| i64::from(data[index])).collect();
+    let mut found = vec![];
+    sorted_k_sum(
+        &sorted,
+        0,
+        k,
+        i64::from(target),
+        &mut vec![],
+        &mut |positions| {
+            let indices = positions.iter().map(|&position| order[position]).collect();
+            found.push(Combination::new(data, indices));
+            search == Search::All
+        },
+    );
+    found.sort_by(|a, b| a.indices.cmp(&b.indices));
+    found
+}
+
+/// Hands the positions of every `k` values of `sorted[start..]` summing to
+/// `target` to `found`, as long as it returns `true`. Returns `false` once
+/// it did not.
+fn sorted_k_sum(
+    sorted: &[i64],
+    start: usize,
+    k: usize,
+    target: i64,
+    chosen: &mut Vec<usize>,
+    found: &mut dyn FnMut(&[usize]) -> bool,
+) -> bool {
+    let rest = &sorted[start..];
+    // The smallest and largest values bound every sum
+    if rest.len() < k
+        || rest[..k].iter().sum::<i64>() > target
+        || rest[rest.len() - k..].iter().sum::<i64>() < target
+    {
+        return true;
+    }
+    let mut emit = |positions: &[usize]| {
+        chosen.extend_from_slice(positions);
+        let more = found(chosen);
+        chosen.truncate(chosen.len() - positions.len());
+        more
+    };
+    match k {
+        1 => {
+            let low = start + rest.partition_point(|&value| value < target);
+            let high = start + rest.partition_point(|&value| value <= target);
+            (low..high).all(|position| emit(&[position]))
+        }
+        2 => {
+            let (mut low, mut high) = (start, sorted.len() - 1);
+            while low < high {
+                let sum = sorted[low] + sorted[high];
+                if sum < target {
+                    low += 1;
+                } else if sum > target {
+                    high -= 1;
+                } else if sorted[low] == sorted[high] {
+                    // Any two of the values in between match
+                    return (low..=high)
+                        .all(|first| (first + 1..=high).all(|second| emit(&[first, second])));
+                } else {
+                    // Every value of the run at `low` matches every value of
+                    // the run at `high`
+                    let low_end = low
+                        + sorted[low..]
+                            .iter()
+                            .take_while(|&&v| v == sorted[low])
+                            .count();
+                    let high_start = high + 1
+                        - sorted[..=high]
+                            .iter()
+                            .rev()
+                            .take_while(|&&v| v == sorted[high])
+                            .count();
+                    if !(low..low_end)
+                        .all(|first| (high_start..=high).all(|second| emit(&[first, second])))
+                    {
+                        return false;
+                    }
+                    low = low_end;
+                    high = high_start - 1;
+                }
+            }
+            true
+        }
+        _ => (start..sorted.len()).all(|first| {
+            chosen.push(first);
+            let more = sorted_k_sum(
+                sorted,
+                first + 1,
+                k - 1,
+                target - sorted[first],
+                chosen,
+                found,
+            );
+            chosen.pop();
+            more
+        }),
+    }
+}
+
+/// Hands every increasing `size` indices below `len` to `f`, as long as it
+/// returns `true`.
+fn for_each_subset<F: FnMut(&[usize]) -> bool>(len: usize, size: usize, mut f: F) {
+    if size > len {
+        return;
+    }
+    let mut subset: Vec<usize> = (0..size).collect();
+    loop {
+        if !f(&subset) {
+            return;
+        }
+        // Advance the last index that can still move, resetting those after it
+        match (0..size).rev().find(|&i| subset[i] < len - size + i) {
+            Some(i) => {
+                subset[i] += 1;
+                for j in i + 1..size {
+                    subset[j] = subset[j - 1] + 1;
+                }
+            }
+            None => return,
+        }
+    }
+}
+
+fn meet_in_the_middle(data: &[i32], target: i64, k: usize) -> Option<Vec<usize>> {
+    let sum = |subset: &[usize]| {
+        subset
+            .iter()
+            .map(|&index| i64::from(data[index]))
+            .sum::<i64>()
+    };
+    let half = k / 2;
+    // The subsets of each sum, one after the other
+    let mut halves: HashMap<i64, Vec<usize>> = HashMap::new();
+    for_each_subset(data.len(), half, |subset| {
+        halves
+            .entry(sum(subset))
+            .or_default()
+            .extend_from_slice(subset);
+        true
+    });
+    let mut found = None;
+    for_each_subset(data.len(), k - half, |subset| {
+        let matching = match halves.get(&(target - sum(subset))) {
+            Some(matching) => matching,
+            None => return true,
+        };
+        match matching
+            .chunks(half)
+            .find(|other| other.iter().all(|index| !subset.contains(index)))
+        {
+            Some(other) => {
+                found = Some(other.iter().chain(subset).copied().collect());
+                false
+            }
+            None => true,
+        }
+    });
+    found
+}
+
+/// The `k` entries of `data` with the largest sum not above `target`.
+///
+/// Like `two_sum`, the entries met so far are kept in a set, ordered to look
+/// up the largest complement fitting in what is left of `target`:
+/// `O(n^(k - 1) log n)` time.
+pub fn closest_k_sum(data: &[i32], target: i32, k: usize) -> Option<Combination> {
+    if k == 0 {
+        return None;
+    }
+    closest_from(data, 0, k, i64::from(target)).map(|(_, indices)| Combination::new(data, indices))
+}
+
+/// The largest sum of `k` entries of `data[start..]` not above `target`,
+/// along with their indices.
+fn closest_from(data: &[i32], start: usize, k: usize, target: i64) -> Option<(i64, Vec<usize>)> {
+    let mut best: Option<(i64, Vec<usize>)> = None;
+    let mut keep = |sum: i64, indices: Vec<usize>| {
+        if best.as_ref().is_none_or(|(best, _)| sum > *best) {
+            best = Some((sum, indices));
+        }
+        sum == target
+    };
+    match k {
+        1 => {
+            for (index, &value) in data.iter().enumerate().skip(start) {
+                if i64::from(value) <= target && keep(i64::from(value), vec![index]) {
+                    break;
+                }
+            }
+        }
+        2 => {
+            let mut seen: BTreeMap<i64, usize> = BTreeMap::new();
+            for (index, &value) in data.iter().enumerate().skip(start) {
+                let value = i64::from(value);
+                if let Some((&other, &other_index)) = seen.range(..=target - value).next_back() {
+                    if keep(value + other, vec![other_index, index]) {
+                        break;
+                    }
+                }
+                seen.entry(value).or_insert(index);
+            }
+        }
+        _ => {
+            for (index, &value) in data.iter().enumerate().skip(start) {
+                let value = i64::from(value);
+                if let Some((sum, mut indices)) =
+                    closest_from(data, index + 1, k - 1, target - value)
+                {
+                    indices.push(index);
+                    if keep(value + sum, indices) {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+    best
+}
+
+/// The sums reachable by adding some entries of `data`, none of them left
+/// above `target` when no entry is negative, as those could only grow.
+fn reachable_sums<T, F>(data: &[i32], target: i64, mut reach: F) -> HashMap<i64, T>
+where
+    F: FnMut(&HashMap<i64, T>, usize, i64, &mut HashMap<i64, T>),
+{
+    let bounded = data.iter().all(|&value| value >= 0);
+    let mut sums = HashMap::new();
+    for (index, &value) in data.iter().enumerate() {
+        let mut next = HashMap::new();
+        reach(&sums, index, i64::from(value), &mut next);
+        if bounded {
+            next.retain(|&sum, _| sum <= target);
+        }
+        sums = next;
+    }
+    sums
+}
+
+/// The number of subsets of entries of `data`, of any size but empty, summing
+/// to `target`. Entries of equal value at different indices make different
+/// subsets. Saturates at `u128::MAX`.
+///
+/// The number of subsets reaching every sum is counted one entry after the
+/// other, in `O(n * s)` time for `s` distinct sums.
+pub fn count_subsets(data: &[i32], target: i32) -> u128 {
+    let target = i64::from(target);
+    let counts = reachable_sums(data, target, |counts, _, value, next| {
+        next.clone_from(counts);
+        for (&sum, &count) in counts {
+            let entry = next.entry(sum + value).or_insert(0u128);
+            *entry = entry.saturating_add(count);
+        }
+        let alone = next.entry(value).or_insert(0u128);
+        *alone = alone.saturating_add(1);
+    });
+    counts.get(&target).copied().unwrap_or(0)
+}
+
+/// Entries of `data`, as many as needed but at least one, summing to
+/// `target`.
+///
+/// Every sum reachable is kept with the last entry reaching it and the sum
+/// before it, like `count_subsets`, then the entries are walked back from
+/// `target`.
+pub fn subset_sum(data: &[i32], target: i32) -> Option<Combination> {
+    let target = i64::from(target);
+    // The last entry added to reach a sum, and the sum before it if any
+    let steps = reachable_sums(data, target, |steps, index, value, next| {
+        next.clone_from(steps);
+        for &sum in steps.keys() {
+            next.entry(sum + value).or_insert((index, Some(sum)));
         }
+        next.entry(value).or_insert((index, None));
+    });
+    let mut indices = vec![];
+    let mut sum = Some(target);
+    while let Some(current) = sum {
+        let &(index, previous) = steps.get(&current)?;
+        indices.push(index);
+        sum = previous;
     }
-    if result != 0 {
-        println!("\tPart 2: {}", result);
+    Some(Combination::new(data, indices))
+}
+
+fn part_1(data: &[i32]) -> Option<i128> {
+    k_sum(data, 2020, 2, Search::First)
+        .first()
+        .and_then(Combination::product)
+}
+
+fn part_2(data: &[i32]) -> Option<i128> {
+    k_sum(data, 2020, 3, Search::First)
+        .first()
+        .and_then(Combination::product)
+}
+
+/// A random expense report of 200 entries with a single pair and a single
+/// triple summing to 2020.
+pub fn generate(rng: &mut Rng) -> String {
+    let mut entries = loop {
+        let a = rng.range(21..=1999) as i32;
+        let x = rng.range(100..=900) as i32;
+        let y = rng.range(100..=1819 - x as i64) as i32;
+        let planted = vec![a, 2020 - a, x, y, 2020 - x - y];
+        let pairs = (0..5)
+            .flat_map(|i| (i + 1..5).map(move |j| (i, j)))
+            .filter(|&(i, j)| planted[i] + planted[j] == 2020)
+            .count();
+        let triples = (0..5)
+            .flat_map(|i| (i + 1..5).flat_map(move |j| (j + 1..5).map(move |k| (i, j, k))))
+            .filter(|&(i, j, k)| planted[i] + planted[j] + planted[k] == 2020)
+            .count();
+        let distinct: HashSet<i32> = planted.iter().copied().collect();
+        if pairs == 1 && triples == 1 && distinct.len() == 5 {
+            break planted;
+        }
+    };
+    let mut set: HashSet<i32> = entries.iter().copied().collect();
+    while entries.len() < 200 {
+        // Mostly large entries, as few small ones can sum to 2020 with others
+        let entry = if rng.chance(0.1) {
+            rng.range(1..=999)
+        } else {
+            rng.range(1000..=2019)
+        } as i32;
+        let sums_to_2020 = set.contains(&(2020 - entry))
+            || entries.iter().any(|&other| {
+                let third = 2020 - entry - other;
+                third != other && set.contains(&third)
+            });
+        if !sums_to_2020 && set.insert(entry) {
+            entries.push(entry);
+        }
     }
+    rng.shuffle(&mut entries);
+    entries.iter().map(|entry| format!("{}\n", entry)).collect()
 }
 
-pub fn main() {
-    println!("Day 1");
-    let data = utilities::read_int_array("../inputs/day1.txt");
-    part_1(&data);
-    part_2(&data);
+pub struct Day1 {
+    data: Vec<i32>,
+}
+
+impl Solution for Day1 {
+    fn parse(input: &str) -> Result<Self> {
+        Ok(Self {
+            data: parse_lines(input)?,
+        })
+    }
+
+    fn part_1(&mut self) -> Result<Answer> {
+        part_1(&self.data)
+            .map(Answer::from)
+            .ok_or_else(|| Error::unsolvable("no two entries sum to 2020"))
+    }
+
+    fn part_2(&mut self) -> Result<Answer> {
+        part_2(&self.data)
+            .map(Answer::from)
+            .ok_or_else(|| Error::unsolvable("no three entries sum to 2020"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_two_sum() {
+        let data = [1721, 979, 366, 299, 675, 1456];
+        assert_eq!(two_sum(&data, 2020), Some(514579));
+        assert_eq!(two_sum(&data, 1), None);
+        assert_eq!(two_sum(&[1010], 2020), None);
+        assert_eq!(two_sum(&[100_000, 100_000], 200_000), Some(10_000_000_000));
+        assert_eq!(
+            two_sum(&[i32::MAX, i32::MIN], -1),
+            Some(-(1 << 62) + (1 << 31))
+        );
+    }
+
+    #[test]
+    fn test_two_sum_brute_force() {
+        let mut rng = Rng::new(3);
+        for _ in 0..1000 {
+            let len = rng.below(20);
+            let data: Vec<i32> = (0..len).map(|_| rng.range(-50..=50) as i32).collect();
+            let target = rng.range(-100..=100) as i32;
+            let products: Vec<i64> = (0..len)
+                .flat_map(|i| (i + 1..len).map(move |j| (i, j)))
+                .filter(|&(i, j)| data[i] + data[j] == target)
+                .map(|(i, j)| i64::from(data[i]) * i64::from(data[j]))
+                .collect();
+            match two_sum(&data, target) {
+                Some(product) => assert!(products.contains(&product), "{:?}", data),
+                None => assert!(products.is_empty(), "{:?}", data),
+            }
+        }
+    }
+
+    #[test]
+    fn test_k_sum() {
+        let data = [1721, 979, 366, 299, 675, 1456];
+        assert_eq!(
+            k_sum(&data, 2020, 2, Search::All),
+            vec![Combination {
+                indices: vec![0, 3],
+                values: vec![1721, 299],
+            }]
+        );
+        let triple = &k_sum(&data, 2020, 3, Search::First)[0];
+        assert_eq!(triple.values, vec![979, 366, 675]);
+        assert_eq!(triple.product(), Some(241861950));
+        let huge = Combination::new(&[i32::MAX; 5], vec![0, 1, 2, 3, 4]);
+        assert_eq!(huge.product(), None);
+        assert_eq!(k_sum(&data, 0, 0, Search::All), vec![]);
+        assert_eq!(k_sum(&data, 5496, 7, Search::All), vec![]);
+        // Equal entries at different positions
+        assert_eq!(k_sum(&[5, 5, 5], 10, 2, Search::All).len(), 3);
+        assert_eq!(k_sum(&[1, 1, 2, 2], 3, 2, Search::All).len(), 4);
+    }
+
+    #[test]
+    fn test_k_sum_brute_force() {
+        let mut rng = Rng::new(11);
+        for _ in 0..300 {
+            let len = rng.below(12);
+            let data: Vec<i32> = (0..len).map(|_| rng.range(-6..=6) as i32).collect();
+            let target = rng.range(-10..=10) as i32;
+            for k in 1..=5 {
+                let mut expected = vec![];
+                for_each_subset(len, k, |subset| {
+                    if subset.iter().map(|&index| data[index]).sum::<i32>() == target {
+                        expected.push(Combination::new(&data, subset.to_vec()));
+                    }
+                    true
+                });
+                let all = k_sum(&data, target, k, Search::All);
+                assert_eq!(all, expected, "{:?}, {}, {}", data, target, k);
+                let first = k_sum(&data, target, k, Search::First);
+                assert_eq!(first.len(), expected.len().min(1));
+                assert!(first.iter().all(|found| expected.contains(found)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_closest_k_sum() {
+        let data = [1721, 979, 366, 299, 675, 1456];
+        let closest = closest_k_sum(&data, 2020, 2).unwrap();
+        assert_eq!(closest.values, vec![1721, 299]);
+        let closest = closest_k_sum(&data, 2000, 2).unwrap();
+        assert_eq!(closest.values, vec![366, 1456]);
+        assert_eq!(
+            closest_k_sum(&data, 2000, 3).unwrap().values,
+            vec![979, 299, 675]
+        );
+        assert_eq!(closest_k_sum(&data, 600, 2), None);
+        assert_eq!(closest_k_sum(&data, 2020, 0), None);
+        assert_eq!(closest_k_sum(&data, 10000, 7), None);
+    }
+
+    #[test]
+    fn test_closest_k_sum_brute_force() {
+        let mut rng = Rng::new(13);
+        for _ in 0..300 {
+            let len = rng.below(10);
+            let data: Vec<i32> = (0..len).map(|_| rng.range(-20..=20) as i32).collect();
+            let target = rng.range(-30..=30) as i32;
+            for k in 1..=4 {
+                let mut best = None;
+                for_each_subset(len, k, |subset| {
+                    let sum: i32 = subset.iter().map(|&index| data[index]).sum();
+                    if sum <= target && best.is_none_or(|best| sum > best) {
+                        best = Some(sum);
+                    }
+                    true
+                });
+                let closest = closest_k_sum(&data, target, k);
+                let sum = closest.as_ref().map(|found| found.values.iter().sum());
+                assert_eq!(sum, best, "{:?}, {}, {}", data, target, k);
+                if let Some(found) = closest {
+                    assert_eq!(found.indices.len(), k);
+                    assert!(found.indices.windows(2).all(|pair| pair[0] < pair[1]));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_subsets() {
+        let data = [1721, 979, 366, 299, 675, 1456];
+        assert_eq!(count_subsets(&data, 2020), 2);
+        assert_eq!(count_subsets(&data, 5496), 1);
+        assert_eq!(count_subsets(&data, 0), 0);
+        assert_eq!(count_subsets(&[0, 0], 0), 3);
+        assert_eq!(count_subsets(&[1; 200], 100), u128::MAX);
+        let subset = subset_sum(&data, 2020).unwrap();
+        assert_eq!(subset.values.iter().sum::<i32>(), 2020);
+        assert_eq!(
+            subset_sum(&data, 5496).unwrap().indices,
+            vec![0, 1, 2, 3, 4, 5]
+        );
+        assert_eq!(subset_sum(&data, 1), None);
+        assert_eq!(subset_sum(&[], 0), None);
+    }
+
+    #[test]
+    fn test_subsets_brute_force() {
+        let mut rng = Rng::new(17);
+        for _ in 0..300 {
+            let len = rng.below(12);
+            let low = if rng.chance(0.5) { 0 } else { -8 };
+            let data: Vec<i32> = (0..len).map(|_| rng.range(low..=8) as i32).collect();
+            let target = rng.range(-10..=30) as i32;
+            let mut count = 0;
+            for size in 1..=len {
+                for_each_subset(len, size, |subset| {
+                    if subset.iter().map(|&index| data[index]).sum::<i32>() == target {
+                        count += 1;
+                    }
+                    true
+                });
+            }
+            assert_eq!(
+                count_subsets(&data, target),
+                count,
+                "{:?}, {}",
+                data,
+                target
+            );
+            match subset_sum(&data, target) {
+                Some(subset) => {
+                    assert_eq!(subset.values.iter().sum::<i32>(), target, "{:?}", data);
+                    assert!(subset.indices.windows(2).all(|pair| pair[0] < pair[1]));
+                }
+                None => assert_eq!(count, 0, "{:?}, {}", data, target),
+            }
+        }
+    }
 }
@@ -1,6 +1,10 @@
-use crate::utilities::read_int_array;
+use crate::error::Result;
+use crate::generate::Rng;
+use crate::solution::{Answer, Solution};
+use crate::utilities::parse_lines;
 
-fn find_differences_product(adapters: &Vec<i64>) -> i64 {
+/// `adapters` must be sorted, the outlet and the device are implied.
+pub fn find_differences_product(adapters: &[i64]) -> i64 {
     let mut prev = 0;
     let mut diff_1 = 0;
     let mut diff_3 = 1;
@@ -15,9 +19,10 @@ fn find_differences_product(adapters: &Vec<i64>) -> i64 {
     diff_1 * diff_3
 }
 
-fn find_num_of_arrangments(adapters: &Vec<i64>) -> i64 {
+/// `adapters` must be sorted, the outlet and the device are implied.
+pub fn find_num_of_arrangments(adapters: &[i64]) -> i64 {
     let mut arrangements = vec![0; adapters.len()];
-    for i in 0..3 {
+    for i in 0..adapters.len().min(3) {
         if adapters[i] <= 3 {
             arrangements[i] = 1;
         }
@@ -29,21 +34,54 @@ fn find_num_of_arrangments(adapters: &Vec<i64>) -> i64 {
             }
         }
     }
-    arrangements.pop().unwrap()
+    // Without adapters the device plugs straight into the outlet
+    arrangements.pop().unwrap_or(1)
 }
 
-fn part_1(adapters: &Vec<i64>) {
-    println!("\tPart 1: {}", find_differences_product(adapters));
+/// A random bag of about 100 adapters, 1 or 3 jolts apart, with at most
+/// 10^14 arrangements.
+pub fn generate(rng: &mut Rng) -> String {
+    // Arrangements of a run of 1 jolt differences between differences of 3
+    const RUN_ARRANGEMENTS: [i64; 5] = [1, 1, 2, 4, 7];
+    let mut adapters = vec![];
+    let mut arrangements = 1;
+    let mut joltage = 0;
+    while adapters.len() < 100 {
+        let mut ones = rng.below(RUN_ARRANGEMENTS.len());
+        if arrangements * RUN_ARRANGEMENTS[ones] > 100_000_000_000_000 {
+            ones = 1;
+        }
+        arrangements *= RUN_ARRANGEMENTS[ones];
+        for _ in 0..ones {
+            joltage += 1;
+            adapters.push(joltage);
+        }
+        joltage += 3;
+        adapters.push(joltage);
+    }
+    rng.shuffle(&mut adapters);
+    adapters
+        .iter()
+        .map(|adapter| format!("{}\n", adapter))
+        .collect()
 }
 
-fn part_2(adapters: &Vec<i64>) {
-    println!("\tPart 2: {}", find_num_of_arrangments(adapters));
+pub struct Day10 {
+    adapters: Vec<i64>,
 }
 
-pub fn main() {
-    println!("Day 10");
-    let mut adapters = read_int_array("../inputs/day10.txt");
-    adapters.sort();
-    part_1(&adapters);
-    part_2(&adapters);
+impl Solution for Day10 {
+    fn parse(input: &str) -> Result<Self> {
+        let mut adapters = parse_lines(input)?;
+        adapters.sort();
+        Ok(Self { adapters })
+    }
+
+    fn part_1(&mut self) -> Result<Answer> {
+        Ok(find_differences_product(&self.adapters).into())
+    }
+
+    fn part_2(&mut self) -> Result<Answer> {
+        Ok(find_num_of_arrangments(&self.adapters).into())
+    }
 }
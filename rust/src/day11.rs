@@ -1,101 +1,104 @@
-use crate::utilities::read_lines;
-use std::mem::swap;
+use crate::automaton::{Bounded, Neighborhood, Rule, Universe};
+use crate::error::{Error, Result};
+use crate::generate::Rng;
+use crate::grid::{Grid, ADJACENT};
+use crate::solution::{Answer, Solution};
 
-fn is_valid_position(layout: &Vec<Vec<char>>, row: isize, col: isize) -> bool {
-    row >= 0 && col >= 0 && row < layout.len() as isize && col < layout[0].len() as isize
+/// Empty seats get taken when no seat around is, and occupied seats are left
+/// by people seeing 4 occupied seats.
+pub const ADJACENT_RULE: Rule = Rule::new(&[0], &[0, 1, 2, 3]);
+/// People looking further only leave when they see 5 occupied seats.
+pub const LINE_OF_SIGHT_RULE: Rule = Rule::new(&[0], &[0, 1, 2, 3, 4]);
+
+const MAX_ROUNDS: usize = 10_000;
+
+/// Seats are live when occupied, the floor holds no cell.
+fn parse_seats(input: &str) -> Result<Grid<Option<bool>>> {
+    let layout = Grid::parse(input, &['.', 'L', '#'])?;
+    Ok(layout.map(|&c| match c {
+        '.' => None,
+        seat => Some(seat == '#'),
+    }))
 }
 
-fn is_direction_occupied(
-    layout: &Vec<Vec<char>>,
-    row: isize,
-    col: isize,
-    direction: (isize, isize),
-    first_visible: bool,
-) -> bool {
-    let row = row + direction.0;
-    let col = col + direction.1;
-    if is_valid_position(layout, row, col) {
-        if layout[row as usize][col as usize] == '#' {
-            return true;
-        }
-        if layout[row as usize][col as usize] == '.' && first_visible {
-            return is_direction_occupied(layout, row, col, direction, first_visible);
-        }
-    }
-    false
+pub fn waiting_area(input: &str, neighborhood: Neighborhood) -> Result<Bounded> {
+    Ok(Bounded::new(parse_seats(input)?, &ADJACENT, neighborhood))
 }
 
-fn count_occupied_around(
-    layout: &Vec<Vec<char>>,
-    row: isize,
-    col: isize,
-    first_visible: bool,
-) -> i32 {
-    let mut occupied = 0;
-    let directions = [
-        (-1, -1),
-        (-1, 0),
-        (-1, 1),
-        (0, -1),
-        (0, 1),
-        (1, -1),
-        (1, 0),
-        (1, 1),
-    ];
-    for &direction in &directions {
-        if is_direction_occupied(layout, row, col, direction, first_visible) {
-            occupied += 1;
-        }
-    }
-    occupied
+fn count_occupied_seats(
+    seats: &Grid<Option<bool>>,
+    neighborhood: Neighborhood,
+    rule: &Rule,
+) -> Result<usize> {
+    let mut waiting_area = Bounded::new(seats.clone(), &ADJACENT, neighborhood);
+    waiting_area.settle(rule, MAX_ROUNDS).ok_or_else(|| {
+        Error::unsolvable(format!(
+            "the seats still change after {} rounds",
+            MAX_ROUNDS
+        ))
+    })?;
+    Ok(waiting_area.population())
 }
 
-fn count_occupied_seats(layout: &Vec<Vec<char>>, first_visible: bool, rule: i32) -> usize {
-    let mut new_layout: Vec<Vec<char>> = layout.iter().map(|l| l.clone()).collect();
-    let mut layout: Vec<Vec<char>> = layout.iter().map(|l| l.clone()).collect();
+/// Random aisles across `len` rows or columns, 5 to 9 apart.
+fn random_aisles(rng: &mut Rng, len: usize) -> Vec<bool> {
+    let mut aisles = vec![false; len];
+    let mut aisle = rng.range(3..=7) as usize;
+    while aisle < len {
+        aisles[aisle] = true;
+        aisle += rng.range(5..=9) as usize;
+    }
+    aisles
+}
 
-    let mut changed = true;
-    while changed {
-        changed = false;
-        for row in 0..layout.len() {
-            for col in 0..layout[0].len() {
-                if layout[row][col] != '.' {
-                    let occupied =
-                        count_occupied_around(&layout, row as isize, col as isize, first_visible);
-                    if layout[row][col] == 'L' && occupied == 0 {
-                        new_layout[row][col] = '#';
-                        changed = true;
-                    } else if layout[row][col] == '#' && occupied >= rule {
-                        new_layout[row][col] = 'L';
-                        changed = true;
-                    } else {
-                        new_layout[row][col] = layout[row][col];
-                    }
-                }
+/// A random layout of about 95 by 95 seats split into blocks by aisles.
+/// Some layouts keep changing forever, so only those settling under both
+/// rules are kept.
+pub fn generate(rng: &mut Rng) -> String {
+    loop {
+        let (width, height) = (rng.range(90..=98) as usize, rng.range(90..=98) as usize);
+        let rows = random_aisles(rng, height);
+        let columns = random_aisles(rng, width);
+        let layout = Grid::from_fn(width, height, |(row, col)| {
+            let floor = if rows[row] || columns[col] { 0.65 } else { 0.1 };
+            if rng.chance(floor) {
+                '.'
+            } else {
+                'L'
             }
+        });
+        let seats = layout.map(|&c| if c == '.' { None } else { Some(false) });
+        let settles = |neighborhood, rule| {
+            let mut waiting_area = Bounded::new(seats.clone(), &ADJACENT, neighborhood);
+            waiting_area.settle(rule, 300).is_some()
+        };
+        if settles(Neighborhood::Adjacent, &ADJACENT_RULE)
+            && settles(Neighborhood::LineOfSight, &LINE_OF_SIGHT_RULE)
+        {
+            return format!("{}\n", layout);
         }
-        swap(&mut layout, &mut new_layout);
     }
-    layout
-        .into_iter()
-        .map(|row| row.into_iter().filter(|c| *c == '#').count())
-        .sum()
 }
 
-fn part_1(layout: &Vec<Vec<char>>) {
-    println!("\tPart 1: {}", count_occupied_seats(layout, false, 4));
+pub struct Day11 {
+    seats: Grid<Option<bool>>,
 }
 
-fn part_2(layout: &Vec<Vec<char>>) {
-    println!("\tPart 2: {}", count_occupied_seats(layout, true, 5));
-}
+impl Solution for Day11 {
+    fn parse(input: &str) -> Result<Self> {
+        Ok(Self {
+            seats: parse_seats(input)?,
+        })
+    }
 
-pub fn main() {
-    println!("Day 11");
-    let layout = read_lines("../inputs/day11.txt")
-        .into_iter()
-        .map(|s| s.chars().collect())
-        .collect();
-    part_1(&layout);
-    part_2(&layout);
+    fn part_1(&mut self) -> Result<Answer> {
+        Ok(count_occupied_seats(&self.seats, Neighborhood::Adjacent, &ADJACENT_RULE)?.into())
+    }
+
+    fn part_2(&mut self) -> Result<Answer> {
+        Ok(
+            count_occupied_seats(&self.seats, Neighborhood::LineOfSight, &LINE_OF_SIGHT_RULE)?
+                .into(),
+        )
+    }
 }
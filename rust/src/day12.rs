@@ -1,20 +1,62 @@
-use crate::utilities::read_lines;
+use crate::error::{Error, Result};
+use crate::generate::Rng;
+use crate::solution::{Answer, Solution};
+use crate::utilities::{map_lines, parse_number};
 
 const DIRECTIONS: [char; 4] = ['N', 'E', 'S', 'W'];
-const DIRECTION_FACTORS: [(i32, i32); 4] = [(0, 1), (1, 0), (0, -1), (-1, 0)];
+const DIRECTION_FACTORS: [(i64, i64); 4] = [(0, 1), (1, 0), (0, -1), (-1, 0)];
+/// Largest value of an instruction, far above those of the puzzle
+const MAX_VALUE: i64 = 1_000_000;
 
-fn calculate_manhattan_distance(instructions: &Vec<String>) -> i32 {
-    let (mut x, mut y) = (0i32, 0i32); // Starting position
+fn parse_instruction(line: &str) -> Result<(char, i64)> {
+    let action = match line.chars().next() {
+        Some(action) if "NESWLRF".contains(action) => action,
+        _ => return Err(Error::parse(format!("unknown action in `{}`", line)).at(line, line)),
+    };
+    let value: i64 = parse_number(line, &line[1..])?;
+    if !(0..=MAX_VALUE).contains(&value) {
+        return Err(Error::parse(format!(
+            "`{}` is not a value between 0 and {}",
+            value, MAX_VALUE
+        ))
+        .at(line, &line[1..]));
+    }
+    if (action == 'L' || action == 'R') && ![90, 180, 270].contains(&value) {
+        return Err(Error::parse(format!("cannot turn by {} degrees", value)).at(line, &line[1..]));
+    }
+    Ok((action, value))
+}
 
-    let mut direction_index = 1usize; // Initially facing east
+fn too_far() -> Error {
+    Error::unsolvable("the ship sails beyond 64-bit coordinates")
+}
+
+/// Moves `position` by `value` times `(dx, dy)`.
+fn advance(position: (i64, i64), value: i64, (dx, dy): (i64, i64)) -> Result<(i64, i64)> {
+    let x = value
+        .checked_mul(dx)
+        .and_then(|dx| position.0.checked_add(dx));
+    let y = value
+        .checked_mul(dy)
+        .and_then(|dy| position.1.checked_add(dy));
+    x.zip(y).ok_or_else(too_far)
+}
+
+fn manhattan_distance((x, y): (i64, i64)) -> Result<i64> {
+    x.checked_abs()
+        .zip(y.checked_abs())
+        .and_then(|(x, y)| x.checked_add(y))
+        .ok_or_else(too_far)
+}
 
-    for instruction in instructions {
-        let action = instruction.chars().next().unwrap();
-        let value: i32 = instruction.as_str()[1..].parse().unwrap();
+fn calculate_manhattan_distance(instructions: &[(char, i64)]) -> Result<i64> {
+    let mut position = (0, 0); // Starting position
 
+    let mut direction_index = 1usize; // Initially facing east
+
+    for &(action, value) in instructions {
         if action == 'F' {
-            x += value * DIRECTION_FACTORS[direction_index].0;
-            y += value * DIRECTION_FACTORS[direction_index].1;
+            position = advance(position, value, DIRECTION_FACTORS[direction_index])?;
         } else if action == 'R' {
             direction_index += value as usize / 90;
             direction_index %= 4;
@@ -24,31 +66,25 @@ fn calculate_manhattan_distance(instructions: &Vec<String>) -> i32 {
         } else {
             for i in 0..4 {
                 if DIRECTIONS[i] == action {
-                    x += value * DIRECTION_FACTORS[i].0;
-                    y += value * DIRECTION_FACTORS[i].1;
+                    position = advance(position, value, DIRECTION_FACTORS[i])?;
                     break;
                 }
             }
         }
     }
-    x.abs() + y.abs()
+    manhattan_distance(position)
 }
 
-fn calculate_manhattan_distance_with_waypoint(instructions: &Vec<String>) -> i32 {
-    let (mut x, mut y) = (0i32, 0i32); // Starting position
-    let (mut waypoint_x, mut waypoint_y) = (10i32, 1i32); // Starting waypoint
-
-    for instruction in instructions {
-        let action = instruction.chars().next().unwrap();
-        let value: i32 = instruction.as_str()[1..].parse().unwrap();
+fn calculate_manhattan_distance_with_waypoint(instructions: &[(char, i64)]) -> Result<i64> {
+    let mut position = (0, 0); // Starting position
+    let mut waypoint = (10i64, 1i64); // Starting waypoint
 
+    for &(action, value) in instructions {
         if action == 'F' {
-            x += value * waypoint_x;
-            y += value * waypoint_y;
+            position = advance(position, value, waypoint)?;
         } else if action == 'R' || action == 'L' {
             if value == 180 {
-                waypoint_x = -waypoint_x;
-                waypoint_y = -waypoint_y;
+                waypoint = (-waypoint.0, -waypoint.1);
             } else {
                 let right_rotation = if action == 'R' { value } else { 360 - value };
 
@@ -57,37 +93,76 @@ fn calculate_manhattan_distance_with_waypoint(instructions: &Vec<String>) -> i32
                 } else {
                     (-1, 1)
                 };
-                let tmp = waypoint_x;
-                waypoint_x = factor.0 * waypoint_y;
-                waypoint_y = factor.1 * tmp;
+                waypoint = (factor.0 * waypoint.1, factor.1 * waypoint.0);
             }
         } else {
             for i in 0..4 {
                 if DIRECTIONS[i] == action {
-                    waypoint_x += value * DIRECTION_FACTORS[i].0;
-                    waypoint_y += value * DIRECTION_FACTORS[i].1;
+                    waypoint = advance(waypoint, value, DIRECTION_FACTORS[i])?;
                     break;
                 }
             }
         }
     }
-    x.abs() + y.abs()
+    manhattan_distance(position)
 }
 
-fn part_1(instructions: &Vec<String>) {
-    println!("\tPart 1: {}", calculate_manhattan_distance(instructions));
+/// A random list of about 780 navigation instructions.
+pub fn generate(rng: &mut Rng) -> String {
+    let mut text = String::new();
+    for _ in 0..rng.range(760..=800) {
+        let instruction = match rng.below(5) {
+            0 => format!("F{}", rng.range(1..=100)),
+            1 => format!("{}{}", rng.choose(&['L', 'R']), rng.choose(&[90, 180, 270])),
+            _ => format!("{}{}", rng.choose(&DIRECTIONS), rng.range(1..=5)),
+        };
+        text.push_str(&instruction);
+        text.push('\n');
+    }
+    text
 }
 
-fn part_2(instructions: &Vec<String>) {
-    println!(
-        "\tPart 2: {}",
-        calculate_manhattan_distance_with_waypoint(instructions)
-    );
+pub struct Day12 {
+    instructions: Vec<(char, i64)>,
 }
 
-pub fn main() {
-    println!("Day 12");
-    let instructions = read_lines("../inputs/day12.txt");
-    part_1(&instructions);
-    part_2(&instructions);
+impl Solution for Day12 {
+    fn parse(input: &str) -> Result<Self> {
+        Ok(Self {
+            instructions: map_lines(input, parse_instruction)?,
+        })
+    }
+
+    fn part_1(&mut self) -> Result<Answer> {
+        Ok(calculate_manhattan_distance(&self.instructions)?.into())
+    }
+
+    fn part_2(&mut self) -> Result<Answer> {
+        Ok(calculate_manhattan_distance_with_waypoint(&self.instructions)?.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bounds() {
+        let error = |input: &str| Day12::parse(input).err().map(|error| error.to_string());
+        assert_eq!(
+            error("F10\nF2000000000"),
+            Some("line 2, column 2: `2000000000` is not a value between 0 and 1000000".to_string())
+        );
+        assert!(error("N-3").is_some());
+
+        // Flying with the waypoint pushed further and further away
+        let mut input = "N1000000\n".repeat(20_000);
+        input.push_str(&"F1000000\n".repeat(1_000));
+        let mut day = Day12::parse(&input).unwrap();
+        assert!(day.part_1().is_ok());
+        assert_eq!(
+            day.part_2().unwrap_err().to_string(),
+            "no solution: the ship sails beyond 64-bit coordinates"
+        );
+    }
 }
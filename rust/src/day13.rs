@@ -1,57 +1,137 @@
-use crate::utilities::read_lines;
-
-fn part_1(earliest_depart: u64, buses: &Vec<(u64, u64)>) {
-    let mut min_wait = 10_000_000;
-    let mut bus_id = 0;
-    for bus in buses {
-        if earliest_depart % bus.0 == 0 {
-            bus_id = bus.0;
-            min_wait = 0;
-            break;
-        } else {
-            let depart = (earliest_depart / bus.0 + 1) * bus.0;
-            let wait = depart - earliest_depart;
-            if wait < min_wait {
-                min_wait = wait;
-                bus_id = bus.0;
-            }
-        }
-    }
-    println!("\tPart 1: {}", min_wait * bus_id);
+use crate::error::{Error, Result};
+use crate::generate::Rng;
+use crate::solution::{Answer, Solution};
+use crate::utilities::{lines, parse_number};
+
+/// Buses are given as `(bus id, offset in the schedule)`. Returns the id of
+/// the first bus leaving at or after `earliest_depart` times the wait, or
+/// `None` when there is no bus or the product overflows.
+pub fn earliest_bus(earliest_depart: u64, buses: &[(u64, u64)]) -> Option<u64> {
+    let (wait, bus_id) = buses
+        .iter()
+        .map(|&(bus_id, _)| ((bus_id - earliest_depart % bus_id) % bus_id, bus_id))
+        .min_by_key(|&(wait, _)| wait)?;
+    wait.checked_mul(bus_id)
 }
 
-fn part_2(buses: &Vec<(u64, u64)>) {
-    let product = buses.iter().fold(1, |acc, bus| acc * bus.0);
-    let mut result = 0;
+/// Finds the earliest timestamp at which every bus leaves at its offset, using
+/// the Chinese remainder theorem. Returns `None` when the bus ids are not
+/// pairwise coprime or their product overflows.
+pub fn earliest_timestamp(buses: &[(u64, u64)]) -> Option<u64> {
+    let product = buses
+        .iter()
+        .try_fold(1u64, |acc, bus| acc.checked_mul(bus.0))?;
+    let mut result = 0u128;
 
     for &(bus_id, wait) in buses {
+        if bus_id == 1 {
+            // Every timestamp satisfies this bus
+            continue;
+        }
         let b = (bus_id - wait % bus_id) % bus_id;
         let n = product / bus_id;
-        let mut xi = n;
+        let mut xi = n % bus_id;
         let mut x = 1;
-        while xi % bus_id != 1 {
-            xi += n;
+        while xi != 1 {
+            if x == bus_id {
+                // `n` has no inverse modulo `bus_id`
+                return None;
+            }
+            xi = (xi + n) % bus_id;
             x += 1;
         }
-        result += b * n * x;
+        result = (result + b as u128 * n as u128 * x as u128) % product as u128;
     }
 
-    result %= product;
+    Some(result as u64)
+}
+
+/// A random timestamp and schedule of 9 buses with prime ids, small enough
+/// for their product to fit in 64 bits.
+pub fn generate(rng: &mut Rng) -> String {
+    let is_prime = |n: &i64| (2..*n).take_while(|d| d * d <= *n).all(|d| n % d != 0);
+    let mut small: Vec<i64> = (13..50).filter(is_prime).collect();
+    let mut large: Vec<i64> = (300..1000).filter(is_prime).collect();
+    rng.shuffle(&mut small);
+    rng.shuffle(&mut large);
+    let mut ids: Vec<i64> = small[..7].iter().chain(&large[..2]).copied().collect();
+    rng.shuffle(&mut ids);
+
+    let mut offsets: Vec<usize> = (1..70).collect();
+    rng.shuffle(&mut offsets);
+    offsets[8] = 0;
+    let mut schedule = vec!["x".to_string(); offsets[..9].iter().max().unwrap() + 1];
+    for (&offset, id) in offsets[..9].iter().zip(&ids) {
+        schedule[offset] = id.to_string();
+    }
+    format!(
+        "{}\n{}\n",
+        rng.range(1_000_000..=1_010_000),
+        schedule.join(",")
+    )
+}
 
-    println!("\tPart 2: {}", result);
+pub struct Day13 {
+    earliest_depart: u64,
+    buses: Vec<(u64, u64)>,
 }
 
-pub fn main() {
-    println!("Day 13");
-    let data = read_lines("../inputs/day13.txt");
-    let earliest_depart: u64 = data[0].parse().unwrap();
-    let buses: Vec<(u64, u64)> = data[1]
-        .split(',')
-        .enumerate()
-        .filter(|x| x.1 != "x")
-        .map(|x| (x.1.parse::<u64>().unwrap(), x.0 as u64))
-        .collect();
-
-    part_1(earliest_depart, &buses);
-    part_2(&buses);
+impl Solution for Day13 {
+    fn parse(input: &str) -> Result<Self> {
+        let mut lines = lines(input);
+        let (first, second) = match (lines.next(), lines.next()) {
+            (Some(first), Some(second)) => (first, second),
+            _ => return Err(Error::parse("expected the timestamp and the bus ids").on_line(1)),
+        };
+        let earliest_depart: u64 = parse_number(first, first).map_err(|e| e.on_line(1))?;
+        let mut buses: Vec<(u64, u64)> = vec![];
+        for (index, bus) in second.split(',').enumerate() {
+            if bus != "x" {
+                let bus_id = parse_number(second, bus).map_err(|e| e.on_line(2))?;
+                if bus_id == 0 {
+                    return Err(Error::parse("bus ids must be positive")
+                        .at(second, bus)
+                        .on_line(2));
+                }
+                buses.push((bus_id, index as u64));
+            }
+        }
+        if buses.is_empty() {
+            return Err(Error::parse("there is no bus in service").on_line(2));
+        }
+        Ok(Self {
+            earliest_depart,
+            buses,
+        })
+    }
+
+    fn part_1(&mut self) -> Result<Answer> {
+        earliest_bus(self.earliest_depart, &self.buses)
+            .map(Answer::from)
+            .ok_or_else(|| Error::unsolvable("the wait times the bus id overflows 64 bits"))
+    }
+
+    fn part_2(&mut self) -> Result<Answer> {
+        earliest_timestamp(&self.buses)
+            .map(Answer::from)
+            .ok_or_else(|| Error::unsolvable("the bus ids are not pairwise coprime"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_earliest_bus() {
+        let buses = [(7, 0), (13, 1), (59, 4), (31, 6), (19, 7)];
+        assert_eq!(earliest_bus(939, &buses), Some(295));
+        assert_eq!(
+            earliest_bus(5, &[(20000000, 1), (20000003, 2)]),
+            Some(399_999_900_000_000)
+        );
+        assert_eq!(earliest_bus(14, &[(7, 0)]), Some(0));
+        assert_eq!(earliest_bus(1, &[(u64::MAX, 0)]), None);
+        assert_eq!(earliest_bus(1, &[]), None);
+    }
 }
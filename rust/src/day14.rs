@@ -1,28 +1,80 @@
-use crate::utilities::read_lines;
+use crate::error::{Error, Result};
+use crate::generate::Rng;
+use crate::solution::{Answer, Solution};
+use crate::utilities::{map_lines, parse_number, split_pair};
 use std::collections::HashMap;
 
 const SIZE: usize = 36;
 
+enum Instruction {
+    /// The bits a mask sets to 1, and its floating bits, the `X`s
+    Mask {
+        ones: u64,
+        floating: u64,
+    },
+    Write {
+        address: u64,
+        value: u64,
+    },
+}
+
+fn parse_instruction(line: &str) -> Result<Instruction> {
+    let (target, value) = split_pair(line, line, " = ")?;
+    if target == "mask" {
+        if value.len() != SIZE || !value.chars().all(|c| c == '0' || c == '1' || c == 'X') {
+            return Err(Error::parse(format!(
+                "expected a mask of {} `0`, `1` or `X`, found `{}`",
+                SIZE, value
+            ))
+            .at(line, value));
+        }
+        let bits = |bit: char| {
+            value
+                .chars()
+                .fold(0, |bits, c| bits << 1 | u64::from(c == bit))
+        };
+        Ok(Instruction::Mask {
+            ones: bits('1'),
+            floating: bits('X'),
+        })
+    } else {
+        let address = target
+            .strip_prefix("mem[")
+            .and_then(|target| target.strip_suffix(']'))
+            .ok_or_else(|| {
+                Error::parse(format!(
+                    "expected `mask` or `mem[<address>]`, found `{}`",
+                    target
+                ))
+                .at(line, target)
+            })?;
+        let mut numbers = [0; 2];
+        for (number, token) in numbers.iter_mut().zip([address, value].iter()) {
+            *number = parse_number(line, token)?;
+            if *number >> SIZE != 0 {
+                return Err(
+                    Error::parse(format!("`{}` does not fit in {} bits", number, SIZE))
+                        .at(line, token),
+                );
+            }
+        }
+        Ok(Instruction::Write {
+            address: numbers[0],
+            value: numbers[1],
+        })
+    }
+}
+
 #[derive(PartialEq)]
 enum Mode {
     Version1,
     Version2,
 }
 
-impl Mode {
-    pub fn mask_bit(&self, mask: char, bit: char) -> char {
-        if *self == Mode::Version1 && mask == 'X' {
-            bit
-        } else if *self == Mode::Version2 && mask == '0' {
-            bit
-        } else {
-            mask
-        }
-    }
-}
 struct DockingProgram {
     memory: HashMap<u64, u64>,
-    mask_string: String,
+    ones: u64,
+    floating: u64,
     mode: Mode,
 }
 
@@ -30,134 +82,171 @@ impl DockingProgram {
     pub fn new(mode: Mode) -> DockingProgram {
         DockingProgram {
             memory: HashMap::new(),
-            mask_string: String::new(),
+            ones: 0,
+            floating: 0,
             mode,
         }
     }
 
-    fn extract_memory_address(s: &str) -> u64 {
-        let start_index = s.find('[').unwrap() + 1;
-        let end_index = s.len() - 1;
-        s[start_index..end_index].parse().unwrap()
-    }
-
-    fn mask(&self, value: u64) -> String {
-        if self.mask_string.is_empty() {
-            value.to_string()
-        } else {
-            let mut masked_value = vec!['0'; SIZE];
-            let binary_form = format!("{:0>36b}", value);
-            for (index, (mask, bit)) in self
-                .mask_string
-                .chars()
-                .zip(binary_form.chars())
-                .enumerate()
-            {
-                masked_value[index] = self.mode.mask_bit(mask, bit);
-            }
-            masked_value.into_iter().collect()
-        }
-    }
-
-    fn write_for_v2(&mut self, masked_addr: &mut Vec<char>, value: u64) {
-        if masked_addr.contains(&'X') {
-            for i in 0..SIZE {
-                if masked_addr[i] == 'X' {
-                    masked_addr[i] = '0';
-                    self.write_for_v2(masked_addr, value);
-                    masked_addr[i] = '1';
-                    self.write_for_v2(masked_addr, value);
-                    masked_addr[i] = 'X';
-                    break;
-                }
-            }
-        } else {
-            let addr = masked_addr.iter().collect::<String>();
-            let memory_address = u64::from_str_radix(addr.as_str(), 2).unwrap();
-            self.memory.insert(memory_address, value);
-        }
-    }
-
-    fn write(&mut self, memory_address: u64, value: u64) {
+    fn write(&mut self, address: u64, value: u64) {
         match self.mode {
             Mode::Version1 => {
-                let masked_value = self.mask(value);
-                let value = u64::from_str_radix(masked_value.as_str(), 2).unwrap();
-                self.memory.insert(memory_address, value);
+                let value = value & self.floating | self.ones;
+                self.memory.insert(address, value);
             }
             Mode::Version2 => {
-                let masked_addr = self.mask(memory_address);
-                let mut masked_addr: Vec<char> = masked_addr.chars().collect();
-                self.write_for_v2(&mut masked_addr, value);
+                // Every combination of the floating bits, from all set to
+                // none set
+                let address = (address | self.ones) & !self.floating;
+                let mut floating = self.floating;
+                loop {
+                    self.memory.insert(address | floating, value);
+                    if floating == 0 {
+                        break;
+                    }
+                    floating = (floating - 1) & self.floating;
+                }
             }
         }
     }
 
-    pub fn execute(&mut self, instruction: &str) {
-        if instruction.starts_with("mask") {
-            // Truncate the `mask = ` part in the instruction
-            self.mask_string = instruction[7..].to_string();
-        } else {
-            let mut iter = instruction.split(" = ");
-            let memory_address = Self::extract_memory_address(iter.next().unwrap());
-            let value: u64 = iter.next().unwrap().parse().unwrap();
-            self.write(memory_address, value);
+    pub fn execute(&mut self, instruction: &Instruction) {
+        match *instruction {
+            Instruction::Mask { ones, floating } => {
+                self.ones = ones;
+                self.floating = floating;
+            }
+            Instruction::Write { address, value } => self.write(address, value),
         }
     }
 
-    pub fn get_sum_memory(&self) -> u64 {
-        self.memory.values().sum()
+    pub fn get_sum_memory(&self) -> Result<u64> {
+        self.memory
+            .values()
+            .try_fold(0u64, |sum, &value| sum.checked_add(value))
+            .ok_or_else(|| Error::unsolvable("the sum of the memory overflows 64 bits"))
     }
 }
 
-fn part_1(instructions: &Vec<String>) -> u64 {
-    let mut program = DockingProgram::new(Mode::Version1);
+fn run(instructions: &[Instruction], mode: Mode) -> Result<u64> {
+    let mut program = DockingProgram::new(mode);
     for instruction in instructions {
-        program.execute(instruction.as_str())
+        program.execute(instruction)
     }
     program.get_sum_memory()
 }
 
-fn part_2(instructions: &Vec<String>) -> u64 {
-    let mut program = DockingProgram::new(Mode::Version2);
-    for instruction in instructions {
-        program.execute(instruction.as_str())
+fn part_1(instructions: &[Instruction]) -> Result<u64> {
+    run(instructions, Mode::Version1)
+}
+
+fn part_2(instructions: &[Instruction]) -> Result<u64> {
+    run(instructions, Mode::Version2)
+}
+
+/// A random program of about 100 masks, with up to 9 floating bits, each
+/// followed by a few writes.
+pub fn generate(rng: &mut Rng) -> String {
+    let mut text = String::new();
+    for _ in 0..rng.range(90..=110) {
+        let mut bits: Vec<usize> = (0..SIZE).collect();
+        rng.shuffle(&mut bits);
+        let floating = rng.below(10);
+        let mut mask = ['0'; SIZE];
+        for (index, &bit) in bits.iter().enumerate() {
+            mask[bit] = if index < floating {
+                'X'
+            } else {
+                *rng.choose(&['0', '1'])
+            };
+        }
+        text.push_str(&format!("mask = {}\n", mask.iter().collect::<String>()));
+        for _ in 0..rng.range(1..=6) {
+            let address = rng.range(0..=65535);
+            text.push_str(&format!(
+                "mem[{}] = {}\n",
+                address,
+                rng.range(0..=999_999_999)
+            ));
+        }
     }
+    text
+}
 
-    program.get_sum_memory()
+pub struct Day14 {
+    instructions: Vec<Instruction>,
 }
 
-pub fn main() {
-    println!("Day 14");
-    let instructions = read_lines("../inputs/day14.txt");
+impl Solution for Day14 {
+    fn parse(input: &str) -> Result<Self> {
+        let mut masked = false;
+        let instructions = map_lines(input, |line| {
+            let instruction = parse_instruction(line)?;
+            match instruction {
+                Instruction::Mask { .. } => masked = true,
+                Instruction::Write { .. } if !masked => {
+                    return Err(
+                        Error::parse("expected a mask before the first write").at(line, line)
+                    )
+                }
+                Instruction::Write { .. } => {}
+            }
+            Ok(instruction)
+        })?;
+        Ok(Self { instructions })
+    }
 
-    println!("\tPart 1: {}", part_1(&instructions));
-    println!("\tPart 2: {}", part_2(&instructions));
+    fn part_1(&mut self) -> Result<Answer> {
+        Ok(part_1(&self.instructions)?.into())
+    }
+
+    fn part_2(&mut self) -> Result<Answer> {
+        Ok(part_2(&self.instructions)?.into())
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{part_1, part_2};
+    use super::*;
+
+    fn parse(input: &str) -> Vec<Instruction> {
+        Day14::parse(input).unwrap().instructions
+    }
 
     #[test]
     fn test_part_1() {
-        let instructions = vec![
-            "mask = XXXXXXXXXXXXXXXXXXXXXXXXXXXXX1XXXX0X".to_string(),
-            "mem[8] = 11".to_string(),
-            "mem[7] = 101".to_string(),
-            "mem[8] = 0".to_string(),
-        ];
-        assert_eq!(part_1(&instructions), 165);
+        let instructions = parse(
+            "mask = XXXXXXXXXXXXXXXXXXXXXXXXXXXXX1XXXX0X
+mem[8] = 11
+mem[7] = 101
+mem[8] = 0",
+        );
+        assert_eq!(part_1(&instructions).unwrap(), 165);
     }
 
     #[test]
     fn test_part_2() {
-        let instructions = vec![
-            "mask = 000000000000000000000000000000X1001X".to_string(),
-            "mem[42] = 100".to_string(),
-            "mask = 00000000000000000000000000000000X0XX".to_string(),
-            "mem[26] = 1".to_string(),
-        ];
-        assert_eq!(part_2(&instructions), 208);
+        let instructions = parse(
+            "mask = 000000000000000000000000000000X1001X
+mem[42] = 100
+mask = 00000000000000000000000000000000X0XX
+mem[26] = 1",
+        );
+        assert_eq!(part_2(&instructions).unwrap(), 208);
+    }
+
+    #[test]
+    fn test_parse() {
+        let error = |input: &str| Day14::parse(input).err().map(|error| error.to_string());
+        assert_eq!(
+            error("mem[1] = 5"),
+            Some("line 1, column 1: expected a mask before the first write".to_string())
+        );
+        let mask = "mask = 000000000000000000000000000000000000\n";
+        assert!(error(&format!("{}mem[ 1] = 5\nmem[1] = 5 ", mask)).is_none());
+        assert_eq!(
+            error(&format!("{}mem[68719476736] = 5", mask)),
+            Some("line 2, column 5: `68719476736` does not fit in 36 bits".to_string())
+        );
     }
 }
@@ -1,27 +1,72 @@
+use crate::error::{Error, Result};
+use crate::generate::Rng;
+use crate::solution::{Answer, Solution};
+use crate::utilities::parse_number;
+
+const MEMORY_SIZE: usize = 30_000_000;
+
+/// Marks the numbers not spoken yet in the memory of the game.
+const NEVER_SPOKEN: u32 = u32::MAX;
+
 fn memory_game(starting_numbers: &[usize], last_turn: usize) -> usize {
     let mut last_num = *starting_numbers.last().unwrap();
-    let mut memory = vec![-1isize; 30_000_000];
+    // Apart from the starting numbers, the numbers spoken are ages of at
+    // most `last_turn` turns
+    let size = starting_numbers.iter().max().unwrap().max(&last_turn) + 1;
+    let mut memory = vec![NEVER_SPOKEN; size];
     for i in 0..starting_numbers.len() - 1 {
-        memory[starting_numbers[i]] = i as isize;
+        memory[starting_numbers[i]] = i as u32;
     }
     for turn in starting_numbers.len() - 1..last_turn - 1 {
-        if memory[last_num] == -1 {
-            memory[last_num] = turn as isize;
+        if memory[last_num] == NEVER_SPOKEN {
+            memory[last_num] = turn as u32;
             last_num = 0;
         } else {
             let new_last_num = turn - memory[last_num] as usize;
-            memory[last_num] = turn as isize;
+            memory[last_num] = turn as u32;
             last_num = new_last_num;
         }
     }
     last_num
 }
 
-pub fn main() {
-    println!("Day 15");
-    let starting_numbers = [1, 20, 8, 12, 0, 14];
-    println!("\tPart 1: {}", memory_game(&starting_numbers, 2020));
-    println!("\tPart 2: {}", memory_game(&starting_numbers, 30000000));
+/// A random list of 3 to 7 distinct starting numbers below 20.
+pub fn generate(rng: &mut Rng) -> String {
+    let mut numbers: Vec<String> = (0..20).map(|number: usize| number.to_string()).collect();
+    rng.shuffle(&mut numbers);
+    format!("{}\n", numbers[..rng.range(3..=7) as usize].join(","))
+}
+
+pub struct Day15 {
+    starting_numbers: Vec<usize>,
+}
+
+impl Solution for Day15 {
+    fn parse(input: &str) -> Result<Self> {
+        let line = input.trim_end();
+        let mut starting_numbers = vec![];
+        for num in line.split(',') {
+            let number = parse_number(line, num).map_err(|e| e.on_line(1))?;
+            if number >= MEMORY_SIZE {
+                return Err(Error::parse(format!(
+                    "starting numbers must be below {}",
+                    MEMORY_SIZE
+                ))
+                .at(line, num)
+                .on_line(1));
+            }
+            starting_numbers.push(number);
+        }
+        Ok(Self { starting_numbers })
+    }
+
+    fn part_1(&mut self) -> Result<Answer> {
+        Ok(memory_game(&self.starting_numbers, 2020).into())
+    }
+
+    fn part_2(&mut self) -> Result<Answer> {
+        Ok(memory_game(&self.starting_numbers, 30000000).into())
+    }
 }
 
 #[cfg(test)]
@@ -1,4 +1,9 @@
-use crate::utilities::read_block;
+use crate::error::{Error, Result};
+use crate::generate::Rng;
+use crate::solution::{Answer, Solution};
+use crate::utilities::{
+    map_block, map_lines, numbered_blocks, parse_number, split_first_line, split_pair,
+};
 use std::collections::HashMap;
 
 struct FieldRule {
@@ -7,19 +12,17 @@ struct FieldRule {
 }
 
 impl FieldRule {
-    fn new(rule: &str) -> Self {
-        let mut ranges = rule.split(" or ");
-        Self {
-            first_range: Self::read_range(ranges.next().unwrap()),
-            second_range: Self::read_range(ranges.next().unwrap()),
-        }
+    fn new(rule: &str) -> Result<Self> {
+        let (first, second) = split_pair(rule, rule, " or ")?;
+        Ok(Self {
+            first_range: Self::read_range(rule, first)?,
+            second_range: Self::read_range(rule, second)?,
+        })
     }
 
-    fn read_range(range: &str) -> (i64, i64) {
-        let mut numbers = range.split('-');
-        let low = numbers.next().unwrap().parse().unwrap();
-        let high = numbers.next().unwrap().parse().unwrap();
-        (low, high)
+    fn read_range(rule: &str, range: &str) -> Result<(i64, i64)> {
+        let (low, high) = split_pair(rule, range, "-")?;
+        Ok((parse_number(rule, low)?, parse_number(rule, high)?))
     }
 
     fn validate(&self, value: i64) -> bool {
@@ -27,7 +30,7 @@ impl FieldRule {
             || (self.second_range.0 <= value && self.second_range.1 >= value)
     }
 
-    fn validate_column(&self, tickets: &Vec<Vec<i64>>, index: usize) -> bool {
+    fn validate_column(&self, tickets: &[Vec<i64>], index: usize) -> bool {
         tickets.iter().all(|ticket| self.validate(ticket[index]))
     }
 }
@@ -39,70 +42,50 @@ struct TicketRule {
 }
 
 impl TicketRule {
-    pub fn new(rules: &String) -> Self {
-        let mut fields = HashMap::new();
-        for rule in rules.split('\n') {
-            let mut iter = rule.split(": ");
-            let field = iter.next().unwrap().to_string();
-            fields.insert(field, FieldRule::new(iter.next().unwrap()));
-        }
-        Self {
-            fields,
+    pub fn new(rules: &str) -> Result<Self> {
+        let fields = map_lines(rules, |line| {
+            let (field, rule) = split_pair(line, line, ": ")?;
+            let rule = FieldRule::new(rule).map_err(|error| error.within(line, rule))?;
+            Ok((field.to_string(), rule))
+        })?;
+        Ok(Self {
+            fields: fields.into_iter().collect(),
             field_index: HashMap::new(),
             valid_tickets: Vec::new(),
-        }
+        })
     }
 
     fn validate_value(&self, value: i64) -> bool {
         self.fields.values().any(|field| field.validate(value))
     }
 
-    pub fn sum_invalid(&mut self, nearby_tickets: &String) -> i64 {
-        let tickets: Vec<Vec<i64>> = nearby_tickets
-            .split('\n')
-            .skip(1)
-            .map(|line| {
-                line.split(',')
-                    .map(|n| n.parse::<i64>().unwrap())
-                    .collect::<Vec<i64>>()
-            })
-            .collect();
+    pub fn sum_invalid(&mut self, nearby_tickets: &[Vec<i64>]) -> i64 {
+        self.valid_tickets.clear();
         let mut ans = 0;
-        for ticket in tickets {
+        for ticket in nearby_tickets {
             let mut valid = true;
-            for &value in &ticket {
+            for &value in ticket {
                 if !self.validate_value(value) {
                     valid = false;
                     ans += value;
                 }
             }
             if valid {
-                self.valid_tickets.push(ticket);
+                self.valid_tickets.push(ticket.clone());
             }
         }
         ans
     }
 
-    fn calculate_ticket_product(&mut self, my_ticket: &String) -> i64 {
-        let my_ticket: Vec<i64> = my_ticket
-            .split('\n')
-            .skip(1)
-            .next()
-            .unwrap()
-            .split(',')
-            .map(|n| n.parse().unwrap())
-            .collect();
-
+    /// Fails when the fields cannot be matched to the columns or the product
+    /// overflows.
+    fn calculate_ticket_product(&mut self, my_ticket: &[i64]) -> Result<i64> {
         let mut field_possible_indices = HashMap::new();
         for (field, rule) in &self.fields {
-            for index in 0..my_ticket.len() {
-                if rule.validate_column(&self.valid_tickets, index) {
-                    field_possible_indices
-                        .entry(field.clone())
-                        .or_insert(vec![])
-                        .push(index);
-                }
-            }
+            let indices: Vec<usize> = (0..my_ticket.len())
+                .filter(|&index| rule.validate_column(&self.valid_tickets, index))
+                .collect();
+            field_possible_indices.insert(field.clone(), indices);
         }
         let mut fields_by_len: Vec<(usize, String)> = field_possible_indices
             .iter()
@@ -113,17 +96,26 @@ impl TicketRule {
 
         let mut available_indices = vec![true; my_ticket.len()];
 
-        self.validate_index(
+        self.field_index.clear();
+        if !self.validate_index(
             &fields_by_len,
             &field_possible_indices,
             &mut available_indices,
-        );
+        ) {
+            return Err(Error::unsolvable(
+                "the fields cannot be matched to the columns",
+            ));
+        }
 
         self.field_index
             .iter()
             .filter(|(field, _)| field.starts_with("departure"))
-            .map(|(_, &index)| my_ticket[index])
-            .fold(1, |acc, x| acc * x)
+            .try_fold(1i64, |product, (_, &index)| {
+                product.checked_mul(my_ticket[index])
+            })
+            .ok_or_else(|| {
+                Error::unsolvable("the product of the departure fields overflows 64 bits")
+            })
     }
 
     fn validate_index(
@@ -136,7 +128,7 @@ impl TicketRule {
             true
         } else {
             let field = &fields_by_len[0].1;
-            for &index in field_possible_indices.get(field).unwrap() {
+            for &index in &field_possible_indices[field] {
                 if available_indices[index] {
                     available_indices[index] = false;
                     if self.validate_index(
@@ -155,15 +147,174 @@ impl TicketRule {
     }
 }
 
-pub fn main() {
-    println!("Day 16");
-    let data = read_block("../inputs/day16.txt");
-    let mut ticket_rule = TicketRule::new(&data[0]);
-    println!("\tPart 1: {}", ticket_rule.sum_invalid(&data[2]));
-    println!(
-        "\tPart 2: {}",
-        ticket_rule.calculate_ticket_product(&data[1])
-    );
+/// Parses a block of tickets, skipping its header line.
+fn parse_tickets(block: &str) -> Result<Vec<Vec<i64>>> {
+    let tickets = match split_first_line(block) {
+        Some((_, tickets)) => tickets,
+        None => return Err(Error::parse("expected tickets after the header").on_line(1)),
+    };
+    map_lines(tickets, |line| {
+        line.split(',')
+            .map(|value| parse_number(line, value))
+            .collect()
+    })
+    .map_err(|error| error.shift_lines(1))
+}
+
+const FIELDS: [&str; 20] = [
+    "departure location",
+    "departure station",
+    "departure platform",
+    "departure track",
+    "departure date",
+    "departure time",
+    "arrival location",
+    "arrival station",
+    "arrival platform",
+    "arrival track",
+    "class",
+    "duration",
+    "price",
+    "route",
+    "row",
+    "seat",
+    "train",
+    "type",
+    "wagon",
+    "zone",
+];
+
+/// Random notes on 20 fields, with about 240 nearby tickets of which a
+/// quarter hold an invalid value. Every field has a level: its rule leaves a
+/// hole in the middle of the values, narrower at each level, and its column
+/// only holds values allowed from its level on. Then the field of the first
+/// level allows a single column, the next one a single other column, and so
+/// on.
+pub fn generate(rng: &mut Rng) -> String {
+    let (low, high) = (rng.range(25..=50), rng.range(950..=974));
+    let mut holes = vec![(rng.range(200..=300), rng.range(700..=800))];
+    while holes.len() < FIELDS.len() {
+        let (start, end) = holes[holes.len() - 1];
+        holes.push((start + rng.range(1..=9), end - rng.range(1..=9)));
+    }
+    let bounds: Vec<(i64, i64)> = (0..FIELDS.len())
+        .map(|_| (low - rng.range(0..=20), high + rng.range(0..=20)))
+        .collect();
+    let mut field_levels: Vec<usize> = (0..FIELDS.len()).collect();
+    let mut column_levels = field_levels.clone();
+    rng.shuffle(&mut field_levels);
+    rng.shuffle(&mut column_levels);
+
+    // `witness` asks for a value the previous level does not allow
+    let value = |rng: &mut Rng, level: usize, witness: bool| {
+        let (start, end) = holes[level];
+        if level > 0 && witness {
+            let (previous_start, previous_end) = holes[level - 1];
+            if rng.chance(0.5) {
+                rng.range(previous_start + 1..=start)
+            } else {
+                rng.range(end..=previous_end - 1)
+            }
+        } else if rng.chance(0.5) {
+            rng.range(low..=start)
+        } else {
+            rng.range(end..=high)
+        }
+    };
+    let invalid = |rng: &mut Rng| match rng.below(3) {
+        0 => rng.range(1..=bounds.iter().map(|bound| bound.0).min().unwrap() - 1),
+        1 => rng.range(bounds.iter().map(|bound| bound.1).max().unwrap() + 1..=999),
+        _ => rng.range(holes[holes.len() - 1].0 + 1..=holes[holes.len() - 1].1 - 1),
+    };
+    let ticket = |rng: &mut Rng, witnesses: bool, may_be_invalid: bool| -> String {
+        let mut values: Vec<i64> = column_levels
+            .iter()
+            .map(|&level| {
+                let witness = witnesses || rng.chance(0.1);
+                value(rng, level, witness)
+            })
+            .collect();
+        if may_be_invalid && rng.chance(0.25) {
+            let index = rng.below(values.len());
+            values[index] = invalid(rng);
+        }
+        let values: Vec<String> = values.iter().map(i64::to_string).collect();
+        values.join(",") + "\n"
+    };
+
+    let mut text = String::new();
+    for (name, &level) in FIELDS.iter().zip(&field_levels) {
+        let (start, end) = holes[level];
+        let (lowest, highest) = bounds[level];
+        text.push_str(&format!(
+            "{}: {}-{} or {}-{}\n",
+            name, lowest, start, end, highest
+        ));
+    }
+    text.push_str("\nyour ticket:\n");
+    text.push_str(&ticket(rng, false, false));
+    text.push_str("\nnearby tickets:\n");
+    // The first nearby ticket shows the level of every column
+    text.push_str(&ticket(rng, true, false));
+    for _ in 0..rng.range(230..=250) {
+        text.push_str(&ticket(rng, false, true));
+    }
+    text
+}
+
+pub struct Day16 {
+    ticket_rule: TicketRule,
+    my_ticket: Vec<i64>,
+    nearby_tickets: Vec<Vec<i64>>,
+}
+
+impl Solution for Day16 {
+    fn parse(input: &str) -> Result<Self> {
+        let blocks: Vec<_> = numbered_blocks(input).collect();
+        if blocks.len() != 3 {
+            return Err(Error::parse(format!(
+                "expected rules, your ticket and nearby tickets, found {} blocks",
+                blocks.len()
+            )));
+        }
+        let ticket_rule = map_block(blocks[0].0, blocks[0].1, TicketRule::new)?;
+        let mut my_ticket = map_block(blocks[1].0, blocks[1].1, parse_tickets)?;
+        if my_ticket.len() != 1 {
+            return Err(Error::parse("expected a single ticket of yours").on_line(blocks[1].0 + 1));
+        }
+        let my_ticket = my_ticket.remove(0);
+        let nearby_tickets = map_block(blocks[2].0, blocks[2].1, parse_tickets)?;
+        if let Some(index) = nearby_tickets
+            .iter()
+            .position(|ticket| ticket.len() != my_ticket.len())
+        {
+            return Err(Error::parse(format!(
+                "expected a ticket of {} values, found {}",
+                my_ticket.len(),
+                nearby_tickets[index].len()
+            ))
+            .on_line(blocks[2].0 + index + 2));
+        }
+        Ok(Self {
+            ticket_rule,
+            my_ticket,
+            nearby_tickets,
+        })
+    }
+
+    fn part_1(&mut self) -> Result<Answer> {
+        Ok(self.ticket_rule.sum_invalid(&self.nearby_tickets).into())
+    }
+
+    fn part_2(&mut self) -> Result<Answer> {
+        // Only the valid tickets collected while summing the invalid values
+        // are used to determine the field order
+        self.ticket_rule.sum_invalid(&self.nearby_tickets);
+        Ok(self
+            .ticket_rule
+            .calculate_ticket_product(&self.my_ticket)?
+            .into())
+    }
 }
 
 #[cfg(test)]
@@ -172,7 +323,7 @@ mod tests {
 
     #[test]
     fn test_field_range() {
-        let rule = FieldRule::new("1-3 or 5-7");
+        let rule = FieldRule::new("1-3 or 5-7").unwrap();
         assert!(rule.validate(1));
         assert!(rule.validate(2));
         assert!(rule.validate(3));
@@ -188,13 +339,32 @@ mod tests {
 
     #[test]
     fn test_sum_invalid() {
-        let data = vec![
+        let data = [
             "class: 1-3 or 5-7\nrow: 6-11 or 33-44\nseat: 13-40 or 45-50".to_string(),
             "your ticket:\n7,1,14".to_string(),
             "nearby tickets:\n7,3,47\n40,4,50\n55,2,20\n38,6,12".to_string(),
         ];
 
-        let mut ticket_rule = TicketRule::new(&data[0]);
-        assert_eq!(ticket_rule.sum_invalid(&data[2]), 71);
+        let mut ticket_rule = TicketRule::new(&data[0]).unwrap();
+        let nearby_tickets = parse_tickets(&data[2]).unwrap();
+        assert_eq!(ticket_rule.sum_invalid(&nearby_tickets), 71);
+    }
+
+    #[test]
+    fn test_ticket_product() {
+        let input = |ticket: &str| {
+            format!(
+                "departure a: 0-9999999999 or 0-0\ndeparture b: 0-9999999999 or 0-0\n\n\
+                 your ticket:\n{}\n\nnearby tickets:\n1,1",
+                ticket
+            )
+        };
+        let mut solution = Day16::parse(&input("3,4")).unwrap();
+        assert_eq!(solution.part_2().unwrap(), Answer::from(12i64));
+        let mut solution = Day16::parse(&input("5000000000,5000000000")).unwrap();
+        assert_eq!(
+            solution.part_2().unwrap_err().to_string(),
+            "no solution: the product of the departure fields overflows 64 bits"
+        );
     }
 }
@@ -1,111 +1,190 @@
-use crate::utilities::read_lines;
+use crate::error::{Error, Result};
+use crate::generate::Rng;
+use crate::solution::{Answer, Solution};
+use crate::utilities::{map_lines, parse_number};
 
-#[derive(Default)]
-struct Calculator {
-    nums: Vec<i64>,
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Token {
+    Number(i64),
+    Add,
+    Multiply,
+    Open,
+    Close,
 }
 
-impl Calculator {
-    fn perform_operation(&mut self, operation: char) {
-        if operation == '+' {
-            let a = self.nums.pop().unwrap();
-            let b = self.nums.pop().unwrap();
-            self.nums.push(a + b);
-        } else if operation == '*' {
-            let a = self.nums.pop().unwrap();
-            let b = self.nums.pop().unwrap();
-            self.nums.push(a * b);
+fn overflow() -> Error {
+    Error::unsolvable("the result overflows 64 bits")
+}
+
+/// Evaluates a checked token stream by recursive descent.
+struct Evaluator<'a> {
+    tokens: &'a [Token],
+    position: usize,
+    advanced: bool,
+}
+
+impl Evaluator<'_> {
+    fn next_is(&self, token: Token) -> bool {
+        self.tokens.get(self.position) == Some(&token)
+    }
+
+    fn operand(&mut self) -> Result<i64> {
+        let token = self.tokens.get(self.position).copied();
+        self.position += 1;
+        match token {
+            Some(Token::Number(number)) => Ok(number),
+            Some(Token::Open) => {
+                let value = self.expression()?;
+                self.position += 1;
+                Ok(value)
+            }
+            _ => Err(Error::parse("unexpected token")),
         }
     }
 
-    fn store(&mut self, num: i64) {
-        self.nums.push(num);
+    /// A run of operands joined by `+`, all of `*` as well unless `advanced`.
+    fn term(&mut self) -> Result<i64> {
+        let mut value = self.operand()?;
+        loop {
+            if self.next_is(Token::Add) {
+                self.position += 1;
+                value = value.checked_add(self.operand()?).ok_or_else(overflow)?;
+            } else if !self.advanced && self.next_is(Token::Multiply) {
+                self.position += 1;
+                value = value.checked_mul(self.operand()?).ok_or_else(overflow)?;
+            } else {
+                return Ok(value);
+            }
+        }
     }
 
-    fn get_result(&self) -> i64 {
-        self.nums[0]
+    fn expression(&mut self) -> Result<i64> {
+        let mut value = self.term()?;
+        while self.next_is(Token::Multiply) {
+            self.position += 1;
+            value = value.checked_mul(self.term()?).ok_or_else(overflow)?;
+        }
+        Ok(value)
     }
 }
 
-fn calculate_expression(expr: &str, advanced: bool) -> i64 {
-    if expr.contains('(') {
-        let mut opens = 0;
-        let mut start = 0;
-        for (i, c) in expr.chars().enumerate() {
-            if c == '(' {
-                opens += 1;
-                if opens == 1 {
-                    start = i;
+fn evaluate(tokens: &[Token], advanced: bool) -> Result<i64> {
+    Evaluator {
+        tokens,
+        position: 0,
+        advanced,
+    }
+    .expression()
+}
+
+/// Evaluates an expression left to right, or with `+` taking precedence over
+/// `*` when `advanced`.
+pub fn calculate_expression(expr: &str, advanced: bool) -> Result<i64> {
+    evaluate(&check_expression(expr)?, advanced)
+}
+
+/// Splits the expression into tokens, checking that it only alternates
+/// numbers and `+`/`*` operators, with balanced parentheses.
+fn check_expression(line: &str) -> Result<Vec<Token>> {
+    let error = |message: &str, index: usize| Err(Error::parse(message).at(line, &line[index..]));
+    let mut tokens = vec![];
+    let mut expect_operand = true;
+    let mut opens = 0;
+    let mut chars = line.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        let token = match c {
+            ' ' => continue,
+            '0'..='9' if expect_operand => {
+                let mut end = i + 1;
+                while let Some(&(j, '0'..='9')) = chars.peek() {
+                    end = j + 1;
+                    chars.next();
                 }
-            } else if c == ')' {
+                expect_operand = false;
+                Token::Number(parse_number(line, &line[i..end])?)
+            }
+            '(' if expect_operand => {
+                opens += 1;
+                Token::Open
+            }
+            ')' if !expect_operand && opens > 0 => {
                 opens -= 1;
-                if opens == 0 {
-                    let mut new_expr = expr[..start].to_string();
-                    let sub_expression_result =
-                        calculate_expression(&expr[start + 1..i], advanced).to_string();
-                    new_expr.push_str(sub_expression_result.as_str());
-                    new_expr.push_str(&expr[i + 1..]);
-                    return calculate_expression(new_expr.as_str(), advanced);
-                }
+                Token::Close
             }
-        }
-        unreachable!("Only reach on invalid expression")
-    } else {
-        let mut calculator = Calculator::default();
-        let mut curr_num = String::new();
-        let mut operation = ' ';
-        let mut operation_stack = vec![];
-
-        for c in expr.replace(' ', "").chars() {
-            if c.is_ascii_digit() {
-                curr_num.push(c);
-            } else {
-                if !curr_num.is_empty() {
-                    calculator.store(curr_num.parse().unwrap());
-                    curr_num.clear();
-                }
-
-                if !advanced || operation == '+' {
-                    calculator.perform_operation(operation);
-                } else if advanced {
-                    operation_stack.push(operation)
-                }
-                operation = c;
+            '+' if !expect_operand => {
+                expect_operand = true;
+                Token::Add
             }
-        }
+            '*' if !expect_operand => {
+                expect_operand = true;
+                Token::Multiply
+            }
+            '0'..='9' | '(' | ')' | '+' | '*' => return error("unexpected token", i),
+            _ => return error("unexpected character", i),
+        };
+        tokens.push(token);
+    }
+    if expect_operand {
+        return error("incomplete expression", line.len());
+    }
+    if opens > 0 {
+        return error("unclosed parenthesis", line.len());
+    }
+    Ok(tokens)
+}
+
+fn sum_expressions(expressions: &[Vec<Token>], advanced: bool) -> Result<i64> {
+    expressions.iter().try_fold(0i64, |sum, tokens| {
+        sum.checked_add(evaluate(tokens, advanced)?)
+            .ok_or_else(overflow)
+    })
+}
 
-        if !curr_num.is_empty() {
-            calculator.store(curr_num.parse().unwrap());
+/// A random expression of single digits. Parentheses nest at most twice, and
+/// only a few per level, to keep the results within 64 bits.
+fn random_expression(rng: &mut Rng, depth: usize) -> String {
+    let (max_terms, mut parentheses) = [(6, 2), (4, 1), (3, 0)][depth];
+    let mut expression = String::new();
+    for index in 0..rng.range(2..=max_terms) {
+        if index > 0 {
+            let operator = *rng.choose(&[" + ", " * "]);
+            expression.push_str(operator);
         }
+        if parentheses > 0 && rng.chance(0.3) {
+            parentheses -= 1;
+            expression.push_str(&format!("({})", random_expression(rng, depth + 1)));
+        } else {
+            expression.push_str(&rng.range(2..=9).to_string());
+        }
+    }
+    expression
+}
 
-        operation_stack.push(operation);
+/// A random homework of about 370 expressions.
+pub fn generate(rng: &mut Rng) -> String {
+    (0..rng.range(360..=380))
+        .map(|_| random_expression(rng, 0) + "\n")
+        .collect()
+}
 
-        while !operation_stack.is_empty() {
-            calculator.perform_operation(operation_stack.pop().unwrap());
-        }
+pub struct Day18 {
+    expressions: Vec<Vec<Token>>,
+}
 
-        calculator.get_result()
+impl Solution for Day18 {
+    fn parse(input: &str) -> Result<Self> {
+        Ok(Self {
+            expressions: map_lines(input, check_expression)?,
+        })
     }
-}
 
-pub fn main() {
-    println!("Day 18");
-    let expressions = read_lines("../inputs/day18.txt");
-
-    println!(
-        "\tPart 1: {}",
-        expressions
-            .iter()
-            .map(|expr| calculate_expression(expr.as_str(), false))
-            .sum::<i64>()
-    );
-    println!(
-        "\tPart 2: {}",
-        expressions
-            .iter()
-            .map(|expr| calculate_expression(expr.as_str(), true))
-            .sum::<i64>()
-    );
+    fn part_1(&mut self) -> Result<Answer> {
+        Ok(sum_expressions(&self.expressions, false)?.into())
+    }
+
+    fn part_2(&mut self) -> Result<Answer> {
+        Ok(sum_expressions(&self.expressions, true)?.into())
+    }
 }
 
 #[cfg(test)]
@@ -114,51 +193,93 @@ mod tests {
 
     #[test]
     fn test_calculate_expression() {
-        assert_eq!(calculate_expression("1 + 2 * 3 + 4 * 5 + 6", false), 71);
         assert_eq!(
-            calculate_expression("1 + (2 * 3) + (4 * (5 + 6))", false),
+            calculate_expression("1 + 2 * 3 + 4 * 5 + 6", false).unwrap(),
+            71
+        );
+        assert_eq!(
+            calculate_expression("1 + (2 * 3) + (4 * (5 + 6))", false).unwrap(),
             51
         );
-        assert_eq!(calculate_expression("2 * 3 + (4 * 5)", false), 26);
+        assert_eq!(calculate_expression("2 * 3 + (4 * 5)", false).unwrap(), 26);
         assert_eq!(
-            calculate_expression("5 + (8 * 3 + 9 + 3 * 4 * 3)", false),
+            calculate_expression("5 + (8 * 3 + 9 + 3 * 4 * 3)", false).unwrap(),
             437
         );
         assert_eq!(
-            calculate_expression("5 * 9 * (7 * 3 * 3 + 9 * 3 + (8 + 6 * 4))", false),
+            calculate_expression("5 * 9 * (7 * 3 * 3 + 9 * 3 + (8 + 6 * 4))", false).unwrap(),
             12240
         );
         assert_eq!(
-            calculate_expression("((2 + 4 * 9) * (6 + 9 * 8 + 6) + 6) + 2 + 4 * 2", false),
+            calculate_expression("((2 + 4 * 9) * (6 + 9 * 8 + 6) + 6) + 2 + 4 * 2", false).unwrap(),
             13632
         );
     }
 
+    #[test]
+    fn test_check_expression() {
+        assert!(check_expression("((2 + 4 * 9) * (6 + 9 * 8 + 6) + 6) + 2 + 4 * 2").is_ok());
+        assert_eq!(
+            check_expression("1 + (2 * 3").unwrap_err().column(),
+            Some(11)
+        );
+        assert!(check_expression("1 + * 2").is_err());
+        assert!(check_expression("1 2").is_err());
+        assert!(check_expression("1 - 2").is_err());
+        assert!(check_expression("(1 + 2))").is_err());
+        assert!(check_expression("").is_err());
+        assert_eq!(
+            check_expression("1 + 99999999999999999999")
+                .unwrap_err()
+                .column(),
+            Some(5)
+        );
+    }
+
+    #[test]
+    fn test_overflow() {
+        for &advanced in &[false, true] {
+            assert_eq!(
+                calculate_expression("9223372036854775807 + 1", advanced)
+                    .unwrap_err()
+                    .to_string(),
+                "no solution: the result overflows 64 bits"
+            );
+            assert!(calculate_expression("(4294967296 * 4294967296)", advanced).is_err());
+            let expressions = map_lines("9223372036854775807\n1", check_expression).unwrap();
+            assert!(sum_expressions(&expressions, advanced).is_err());
+        }
+    }
+
     #[test]
     fn test_calculate_expression_advanced() {
-        assert_eq!(calculate_expression("1 + 2 * 3 + 4 * 5 + 6", true), 231);
         assert_eq!(
-            calculate_expression("1 + (2 * 3) + (4 * (5 + 6))", true),
+            calculate_expression("1 + 2 * 3 + 4 * 5 + 6", true).unwrap(),
+            231
+        );
+        assert_eq!(
+            calculate_expression("1 + (2 * 3) + (4 * (5 + 6))", true).unwrap(),
             51
         );
-        assert_eq!(calculate_expression("2 * 3 + (4 * 5)", true), 46);
+        assert_eq!(calculate_expression("2 * 3 + (4 * 5)", true).unwrap(), 46);
         assert_eq!(
-            calculate_expression("5 + (8 * 3 + 9 + 3 * 4 * 3)", true),
+            calculate_expression("5 + (8 * 3 + 9 + 3 * 4 * 3)", true).unwrap(),
             1445
         );
         assert_eq!(
-            calculate_expression("5 * 9 * (7 * 3 * 3 + 9 * 3 + (8 + 6 * 4))", true),
+            calculate_expression("5 * 9 * (7 * 3 * 3 + 9 * 3 + (8 + 6 * 4))", true).unwrap(),
             669060
         );
         assert_eq!(
-            calculate_expression("((2 + 4 * 9) * (6 + 9 * 8 + 6) + 6) + 2 + 4 * 2", true),
+            calculate_expression("((2 + 4 * 9) * (6 + 9 * 8 + 6) + 6) + 2 + 4 * 2", true).unwrap(),
             23340
         );
         assert_eq!(
             calculate_expression(
                 "3 + (2 * 2 + (7 * 3) * 2) + 7 + 4 + (2 + 6 * 4 + 9 * 4 * 5)",
                 true
-            ),
+            )
+            .unwrap(),
             2186
         );
     }
@@ -1,6 +1,12 @@
-use std::{collections::HashMap, mem::swap};
+use std::{
+    collections::{HashMap, HashSet},
+    mem::swap,
+};
 
-use crate::utilities::read_block;
+use crate::error::{Error, Result};
+use crate::generate::Rng;
+use crate::solution::{Answer, Solution};
+use crate::utilities::{lines, map_block, map_lines, numbered_blocks, parse_number, split_pair};
 
 enum Rule {
     Match(char),
@@ -8,23 +14,31 @@ enum Rule {
 }
 
 impl Rule {
-    fn new(rule: &str) -> Rule {
+    fn new(rule: &str) -> Result<Rule> {
         if rule.contains('"') {
-            Rule::Match(rule.chars().skip(1).next().unwrap())
+            let mut chars = rule.chars();
+            match (chars.next(), chars.next(), chars.next(), chars.next()) {
+                (Some('"'), Some(c), Some('"'), None) => Ok(Rule::Match(c)),
+                _ => Err(Error::parse(format!(
+                    "expected a single quoted character, found `{}`",
+                    rule
+                ))
+                .at(rule, rule)),
+            }
         } else {
-            Rule::RuleList(
-                rule.split(" | ")
-                    .map(|list| {
-                        list.split(' ')
-                            .map(|r| r.parse::<usize>().unwrap())
-                            .collect::<Vec<usize>>()
-                    })
-                    .collect(),
-            )
+            let options = rule
+                .split(" | ")
+                .map(|list| {
+                    list.split(' ')
+                        .map(|r| parse_number(rule, r))
+                        .collect::<Result<Vec<usize>>>()
+                })
+                .collect::<Result<_>>()?;
+            Ok(Rule::RuleList(options))
         }
     }
 
-    fn match_message(&self, index: usize, message: &Vec<char>, rule_map: &RuleMap) -> Vec<usize> {
+    fn match_message(&self, index: usize, message: &[char], rule_map: &RuleMap) -> Vec<usize> {
         if index == message.len() {
             vec![]
         } else {
@@ -62,24 +76,44 @@ struct RuleMap {
 }
 
 impl RuleMap {
-    fn new(rules_str: String) -> Self {
-        let mut rules = HashMap::new();
-        for rule_str in rules_str.split('\n') {
-            let mut iter = rule_str.split(": ");
-            let rule = iter.next().unwrap().parse().unwrap();
-            rules.insert(rule, Rule::new(iter.next().unwrap()));
+    fn new(rules_str: &str) -> Result<Self> {
+        let rules: HashMap<usize, Rule> = map_lines(rules_str, |line| {
+            let (id, rule) = split_pair(line, line, ": ")?;
+            let rule = Rule::new(rule).map_err(|error| error.within(line, rule))?;
+            Ok((parse_number(line, id)?, rule))
+        })?
+        .into_iter()
+        .collect();
+
+        if !rules.contains_key(&0) {
+            return Err(Error::parse("rule 0 is missing"));
+        }
+        for (id, rule) in &rules {
+            if let Rule::RuleList(options) = rule {
+                if let Some(missing) = options.iter().flatten().find(|r| !rules.contains_key(r)) {
+                    return Err(Error::parse(format!(
+                        "rule {} refers to the missing rule {}",
+                        id, missing
+                    )));
+                }
+            }
+        }
+        if let Some(cycle) = left_recursion(&rules) {
+            let cycle: Vec<String> = cycle.iter().map(usize::to_string).collect();
+            return Err(Error::parse(format!(
+                "rule {} comes back to itself before matching any character: {}",
+                cycle[0],
+                cycle.join(" -> ")
+            )));
         }
-        Self { rules }
+        Ok(Self { rules })
     }
 
-    fn match_message(&self, rule: usize, index: usize, message: &Vec<char>) -> Vec<usize> {
-        self.rules
-            .get(&rule)
-            .unwrap()
-            .match_message(index, message, self)
+    fn match_message(&self, rule: usize, index: usize, message: &[char]) -> Vec<usize> {
+        self.rules[&rule].match_message(index, message, self)
     }
 
-    fn is_matched(&self, message: &Vec<char>) -> bool {
+    fn is_matched(&self, message: &[char]) -> bool {
         let matched_lengths = self.match_message(0, 0, message);
         matched_lengths
             .into_iter()
@@ -87,38 +121,212 @@ impl RuleMap {
     }
 }
 
-fn count_match_message(rule_string: String, messages: &Vec<Vec<char>>) -> usize {
-    let rule_map = RuleMap::new(rule_string);
-    messages
+/// A cycle of rules, each one starting an alternative of the one before,
+/// which matching would go around forever without reading any character.
+/// Loops such as `8: 42 | 42 8` read the message before coming back.
+fn left_recursion(rules: &HashMap<usize, Rule>) -> Option<Vec<usize>> {
+    fn visit(
+        rules: &HashMap<usize, Rule>,
+        rule: usize,
+        path: &mut Vec<usize>,
+        done: &mut HashSet<usize>,
+    ) -> Option<Vec<usize>> {
+        if let Some(start) = path.iter().position(|&other| other == rule) {
+            let mut cycle = path[start..].to_vec();
+            cycle.push(rule);
+            return Some(cycle);
+        }
+        if done.contains(&rule) {
+            return None;
+        }
+        path.push(rule);
+        if let Some(Rule::RuleList(options)) = rules.get(&rule) {
+            for first in options.iter().filter_map(|option| option.first()) {
+                if let Some(cycle) = visit(rules, *first, path, done) {
+                    return Some(cycle);
+                }
+            }
+        }
+        path.pop();
+        done.insert(rule);
+        None
+    }
+
+    let mut ids: Vec<usize> = rules.keys().copied().collect();
+    ids.sort_unstable();
+    let mut done = HashSet::new();
+    ids.into_iter()
+        .find_map(|id| visit(rules, id, &mut vec![], &mut done))
+}
+
+fn count_match_message(rule_string: &str, messages: &[Vec<char>]) -> Result<usize> {
+    let rule_map = RuleMap::new(rule_string)?;
+    Ok(messages
         .iter()
         .filter(|&message| rule_map.is_matched(message))
-        .count()
+        .count())
 }
 
-pub fn main() {
-    println!("Day 19");
-    let mut data = read_block("../inputs/day19.txt");
-    let messages = data
-        .pop()
-        .unwrap()
-        .split('\n')
-        .map(|s| s.chars().collect::<Vec<char>>())
-        .collect::<Vec<Vec<char>>>();
-    let rule_string = data.pop().unwrap();
-
-    println!(
-        "\tPart 1: {}",
-        count_match_message(rule_string.clone(), &messages)
-    );
-    println!(
-        "\tPart 2: {}",
-        count_match_message(
-            rule_string
-                .replace("8: 42", "8: 42 | 42 8")
-                .replace("11: 42 31", "11: 42 31 | 42 11 31"),
-            &messages
-        )
-    );
+/// Adds two rules to `rules` matching complementary sets of messages of
+/// `len` letters, the first two rules being `"a"` and `"b"`. A message split
+/// after `split` letters matches the first rule if its start matches `prefix`
+/// and its end `first.0`, or its start `other_prefix` and its end
+/// `second.0`; the second rule matches the other messages.
+fn complementary_rules(rng: &mut Rng, rules: &mut Vec<Rule>, len: usize) -> (usize, usize) {
+    if len == 1 {
+        return if rng.chance(0.5) { (0, 1) } else { (1, 0) };
+    }
+    let split = rng.range(1..=len as i64 - 1) as usize;
+    let (prefix, other_prefix) = complementary_rules(rng, rules, split);
+    let first = complementary_rules(rng, rules, len - split);
+    let second = if rng.chance(0.5) {
+        complementary_rules(rng, rules, len - split)
+    } else {
+        first
+    };
+    rules.push(Rule::RuleList(vec![
+        vec![prefix, first.0],
+        vec![other_prefix, second.0],
+    ]));
+    rules.push(Rule::RuleList(vec![
+        vec![prefix, first.1],
+        vec![other_prefix, second.1],
+    ]));
+    (rules.len() - 2, rules.len() - 1)
+}
+
+fn random_match(rng: &mut Rng, rules: &[Rule], rule: usize, message: &mut String) {
+    match &rules[rule] {
+        Rule::Match(c) => message.push(*c),
+        Rule::RuleList(options) => {
+            for &rule in rng.choose(options) {
+                random_match(rng, rules, rule, message);
+            }
+        }
+    }
+}
+
+/// Random rules where, as in the puzzle, `0: 8 11`, `8: 42` and `11: 42 31`,
+/// rules 42 and 31 matching complementary sets of messages of 8 letters. The
+/// messages repeat matches of 42 and 31, or are random.
+pub fn generate(rng: &mut Rng) -> String {
+    let mut rules = vec![Rule::Match('a'), Rule::Match('b')];
+    let (rule_42, rule_31) = complementary_rules(rng, &mut rules, 8);
+    rules.push(Rule::RuleList(vec![vec![rule_42]]));
+    rules.push(Rule::RuleList(vec![vec![rule_42, rule_31]]));
+    rules.push(Rule::RuleList(vec![vec![rules.len() - 2, rules.len() - 1]]));
+
+    // Ids are given at random, save those fixed by the puzzle. There are
+    // less than 420 rules, so no other rule starts with `42` and part 2 only
+    // replaces rules 8 and 11.
+    let len = rules.len();
+    let fixed = [
+        (rule_42, 42),
+        (rule_31, 31),
+        (len - 3, 8),
+        (len - 2, 11),
+        (len - 1, 0),
+    ];
+    let mut free: Vec<usize> = (1..)
+        .filter(|id| fixed.iter().all(|&(_, fixed_id)| fixed_id != *id))
+        .take(len - fixed.len())
+        .collect();
+    rng.shuffle(&mut free);
+    let mut ids = vec![0; len];
+    for (rule, id) in ids.iter_mut().enumerate() {
+        *id = match fixed.iter().find(|&&(fixed_rule, _)| fixed_rule == rule) {
+            Some(&(_, fixed_id)) => fixed_id,
+            None => free.pop().unwrap(),
+        };
+    }
+    let mut lines: Vec<String> = rules
+        .iter()
+        .enumerate()
+        .map(|(index, rule)| {
+            let body = match rule {
+                Rule::Match(c) => format!("\"{}\"", c),
+                Rule::RuleList(options) => options
+                    .iter()
+                    .map(|option| {
+                        let option: Vec<String> =
+                            option.iter().map(|&rule| ids[rule].to_string()).collect();
+                        option.join(" ")
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" | "),
+            };
+            format!("{}: {}", ids[index], body)
+        })
+        .collect();
+    rng.shuffle(&mut lines);
+
+    let mut messages = vec![];
+    for _ in 0..rng.range(400..=480) {
+        let (count_42, count_31) = match rng.below(4) {
+            0 => (2, 1),
+            1 => {
+                let count_42 = rng.range(2..=6);
+                (count_42, rng.range(1..=count_42 - 1))
+            }
+            2 => {
+                let count_42 = rng.range(1..=4);
+                (count_42, rng.range(count_42..=count_42 + 2))
+            }
+            _ => {
+                let len = rng.range(5..=40);
+                messages.push((0..len).map(|_| *rng.choose(&['a', 'b'])).collect());
+                continue;
+            }
+        };
+        let mut message = String::new();
+        for _ in 0..count_42 {
+            random_match(rng, &rules, rule_42, &mut message);
+        }
+        for _ in 0..count_31 {
+            random_match(rng, &rules, rule_31, &mut message);
+        }
+        messages.push(message);
+    }
+    format!("{}\n\n{}\n", lines.join("\n"), messages.join("\n"))
+}
+
+pub struct Day19 {
+    rule_string: String,
+    messages: Vec<Vec<char>>,
+}
+
+impl Solution for Day19 {
+    fn parse(input: &str) -> Result<Self> {
+        let blocks: Vec<_> = numbered_blocks(input).collect();
+        if blocks.len() != 2 {
+            return Err(Error::parse(format!(
+                "expected rules and messages, found {} blocks",
+                blocks.len()
+            )));
+        }
+        let (offset, rule_string) = blocks[0];
+        // Check the rules here to report their errors with the input lines
+        map_block(offset, rule_string, RuleMap::new)?;
+        let messages = lines(blocks[1].1)
+            .map(|s| s.chars().collect::<Vec<char>>())
+            .collect::<Vec<Vec<char>>>();
+        Ok(Self {
+            rule_string: rule_string.into(),
+            messages,
+        })
+    }
+
+    fn part_1(&mut self) -> Result<Answer> {
+        Ok(count_match_message(&self.rule_string, &self.messages)?.into())
+    }
+
+    fn part_2(&mut self) -> Result<Answer> {
+        let rule_string = self
+            .rule_string
+            .replace("8: 42", "8: 42 | 42 8")
+            .replace("11: 42 31", "11: 42 31 | 42 11 31");
+        Ok(count_match_message(&rule_string, &self.messages)?.into())
+    }
 }
 
 #[cfg(test)]
@@ -132,8 +340,7 @@ mod tests {
 2: 4 4 | 5 5
 3: 4 5 | 5 4
 4: "a"
-5: "b""#
-            .to_string();
+5: "b""#;
         let messages_str = r#"ababbb
 bababa
 abbbab
@@ -143,7 +350,7 @@ aaaabbb"#;
             .split('\n')
             .map(|s| s.chars().collect::<Vec<char>>())
             .collect::<Vec<Vec<char>>>();
-        assert_eq!(count_match_message(rule_str, &messages), 2);
+        assert_eq!(count_match_message(rule_str, &messages).unwrap(), 2);
     }
 
     #[test]
@@ -193,21 +400,38 @@ aabbbbbaabbbaaaaaabbbbbababaaaaabbaaabba"#;
 26: 14 22 | 1 20
 18: 15 15
 7: 14 5 | 1 21
-24: 14 1"#
-            .to_string();
+24: 14 1"#;
         let messages = messages_str
             .split('\n')
             .map(|s| s.chars().collect::<Vec<char>>())
             .collect::<Vec<Vec<char>>>();
-        assert_eq!(count_match_message(rule_str.clone(), &messages), 3);
+        assert_eq!(count_match_message(rule_str, &messages).unwrap(), 3);
         assert_eq!(
             count_match_message(
-                rule_str
+                &rule_str
                     .replace("8: 42", "8: 42 | 42 8")
                     .replace("11: 42 31", "11: 42 31 | 42 11 31"),
                 &messages
-            ),
+            )
+            .unwrap(),
             12
         );
     }
+
+    #[test]
+    fn test_left_recursion() {
+        let error = |rules: &str| RuleMap::new(rules).err().map(|error| error.to_string());
+        assert_eq!(
+            error("0: 0"),
+            Some("rule 0 comes back to itself before matching any character: 0 -> 0".to_string())
+        );
+        assert_eq!(
+            error("0: 3 | 1\n1: 2 1\n2: 1 3 | 3\n3: \"a\""),
+            Some(
+                "rule 1 comes back to itself before matching any character: 1 -> 2 -> 1"
+                    .to_string()
+            )
+        );
+        assert!(error("0: 8\n8: 42 | 42 8\n42: \"a\"").is_none());
+    }
 }
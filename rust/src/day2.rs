@@ -1,71 +1,784 @@
-use crate::utilities::read_lines;
+use std::fmt;
+use std::str::FromStr;
 
+use crate::error::{Error, Result};
+use crate::generate::Rng;
+use crate::output::{json_string, table};
+use crate::solution::{Answer, Solution};
+use crate::utilities::{map_lines, parse_number, split_pair};
+
+/// A set of characters a rule looks for.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Class {
+    Char(char),
+    Digit,
+    Lower,
+    Upper,
+    Alpha,
+    Any,
+}
+
+impl Class {
+    pub fn matches(&self, ch: char) -> bool {
+        match self {
+            Class::Char(letter) => ch == *letter,
+            Class::Digit => ch.is_ascii_digit(),
+            Class::Lower => ch.is_lowercase(),
+            Class::Upper => ch.is_uppercase(),
+            Class::Alpha => ch.is_alphabetic(),
+            Class::Any => true,
+        }
+    }
+}
+
+/// A single character stands for itself, the named classes are `digit`,
+/// `lower`, `upper`, `alpha` and `any`.
+impl FromStr for Class {
+    type Err = Error;
+
+    fn from_str(class: &str) -> Result<Class> {
+        let mut chars = class.chars();
+        match (class, chars.next(), chars.next()) {
+            (_, Some(ch), None) => Ok(Class::Char(ch)),
+            ("digit", _, _) => Ok(Class::Digit),
+            ("lower", _, _) => Ok(Class::Lower),
+            ("upper", _, _) => Ok(Class::Upper),
+            ("alpha", _, _) => Ok(Class::Alpha),
+            ("any", _, _) => Ok(Class::Any),
+            _ => Err(Error::parse(format!(
+                "expected a character or a class, found `{}`",
+                class
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for Class {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Class::Char(ch) => write!(f, "{}", ch),
+            Class::Digit => write!(f, "digit"),
+            Class::Lower => write!(f, "lower"),
+            Class::Upper => write!(f, "upper"),
+            Class::Alpha => write!(f, "alpha"),
+            Class::Any => write!(f, "any"),
+        }
+    }
+}
+
+/// An inclusive range of counts, without an upper bound when `max` is
+/// `None`, written `1-3`, `8-` or `2`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Bounds {
+    pub min: usize,
+    pub max: Option<usize>,
+}
+
+impl Bounds {
+    pub fn contains(&self, count: usize) -> bool {
+        count >= self.min && self.max.is_none_or(|max| count <= max)
+    }
+}
+
+impl FromStr for Bounds {
+    type Err = Error;
+
+    fn from_str(bounds: &str) -> Result<Bounds> {
+        let (min, max) = match bounds.split_once('-') {
+            Some((min, "")) => (parse_number(bounds, min)?, None),
+            Some((min, max)) => (parse_number(bounds, min)?, Some(parse_number(bounds, max)?)),
+            None => {
+                let count = parse_number(bounds, bounds)?;
+                (count, Some(count))
+            }
+        };
+        if max.is_some_and(|max| max < min) {
+            return Err(Error::parse(format!("empty range `{}`", bounds)));
+        }
+        Ok(Bounds { min, max })
+    }
+}
+
+impl fmt::Display for Bounds {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.max {
+            Some(max) if max == self.min => write!(f, "{}", max),
+            Some(max) => write!(f, "{}-{}", self.min, max),
+            None => write!(f, "{}-", self.min),
+        }
+    }
+}
+
+/// A password policy, written as in `all(count(a, 1-3), not(forbid(abc)))`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Rule {
+    /// `count(class, bounds)`: the number of characters of the class
+    Count(Class, Bounds),
+    /// `at(position, class)`: the character at a 1-based position
+    At(usize, Class),
+    /// `length(bounds)`: the number of characters
+    Length(Bounds),
+    /// `forbid(text)`: the password must not contain the text, which holds
+    /// no `,`, `(` or `)`
+    Forbid(String),
+    /// `all(rule, ...)`: every rule passes
+    All(Vec<Rule>),
+    /// `any(rule, ...)`: at least one rule passes
+    Any(Vec<Rule>),
+    /// `one(rule, ...)`: exactly one rule passes
+    One(Vec<Rule>),
+    /// `not(rule)`: the rule fails
+    Not(Box<Rule>),
+}
+
+/// The outcome of a rule on a password, along with those of its sub-rules.
+#[derive(Debug, PartialEq)]
+pub struct Check<'a> {
+    pub rule: &'a Rule,
+    pub passed: bool,
+    /// What the rule found in the password, e.g. `4 matches`
+    pub found: String,
+    pub checks: Vec<Check<'a>>,
+}
+
+impl Check<'_> {
+    /// Why the rule failed, going down to the sub-rules failing `all` or
+    /// `any`, or `None` when it passed.
+    pub fn reason(&self) -> Option<String> {
+        if self.passed {
+            return None;
+        }
+        let reasons: Vec<String> = match self.rule {
+            Rule::All(_) | Rule::Any(_) => self.checks.iter().filter_map(Check::reason).collect(),
+            _ => vec![],
+        };
+        if reasons.is_empty() {
+            Some(format!("{}: {}", self.rule, self.found))
+        } else {
+            Some(reasons.join("; "))
+        }
+    }
+}
+
+impl Rule {
+    pub fn passes(&self, password: &str) -> bool {
+        self.check(password).passed
+    }
+
+    /// Checks the password, explaining the outcome of every rule.
+    pub fn check(&self, password: &str) -> Check<'_> {
+        let checks: Vec<Check> = match self {
+            Rule::All(rules) | Rule::Any(rules) | Rule::One(rules) => {
+                rules.iter().map(|rule| rule.check(password)).collect()
+            }
+            Rule::Not(rule) => vec![rule.check(password)],
+            _ => vec![],
+        };
+        let passing = checks.iter().filter(|check| check.passed).count();
+        let (passed, found) = match self {
+            Rule::Count(class, bounds) => {
+                let count = password.chars().filter(|&ch| class.matches(ch)).count();
+                (bounds.contains(count), plural(count, "match", "matches"))
+            }
+            Rule::At(position, class) => match char_at(password, *position) {
+                Some(ch) => (class.matches(ch), format!("`{}`", ch)),
+                None => (false, "no character".to_string()),
+            },
+            Rule::Length(bounds) => {
+                let length = password.chars().count();
+                (
+                    bounds.contains(length),
+                    plural(length, "character", "characters"),
+                )
+            }
+            Rule::Forbid(text) => match password.find(text.as_str()) {
+                Some(index) => (
+                    false,
+                    format!("found at {}", password[..index].chars().count() + 1),
+                ),
+                None => (true, "absent".to_string()),
+            },
+            Rule::All(_) => (
+                passing == checks.len(),
+                passing_rules(passing, checks.len()),
+            ),
+            Rule::Any(_) => (passing > 0, passing_rules(passing, checks.len())),
+            Rule::One(_) => (passing == 1, passing_rules(passing, checks.len())),
+            Rule::Not(_) => {
+                let passed = passing == 0;
+                (passed, if passed { "fails" } else { "passes" }.to_string())
+            }
+        };
+        Check {
+            rule: self,
+            passed,
+            found,
+            checks,
+        }
+    }
+}
+
+fn char_at(password: &str, position: usize) -> Option<char> {
+    password.chars().nth(position.checked_sub(1)?)
+}
+
+fn plural(count: usize, one: &str, many: &str) -> String {
+    format!("{} {}", count, if count == 1 { one } else { many })
+}
+
+fn passing_rules(passing: usize, total: usize) -> String {
+    format!("{} of {} rules pass", passing, total)
+}
+
+impl FromStr for Rule {
+    type Err = Error;
+
+    fn from_str(source: &str) -> Result<Rule> {
+        let (rule, rest) = parse_rule(source, source)?;
+        if !rest.trim().is_empty() {
+            return Err(Error::parse(format!("unexpected `{}`", rest.trim())).at(source, rest));
+        }
+        Ok(rule)
+    }
+}
+
+/// Parses the rule starting `text`, a slice of `source`, returning it along
+/// with the text left after it.
+fn parse_rule<'a>(source: &str, text: &'a str) -> Result<(Rule, &'a str)> {
+    let (name, rest) = split_pair(source, text, "(")?;
+    let name = name.trim();
+    let (arguments, rest) = split_arguments(source, rest)?;
+    let arity = |count: usize| {
+        if arguments.len() == count {
+            Ok(())
+        } else {
+            Err(Error::parse(format!(
+                "`{}` takes {}, found {}",
+                name,
+                plural(count, "argument", "arguments"),
+                arguments.len()
+            ))
+            .at(source, name))
+        }
+    };
+    let class = |argument: &str| {
+        argument
+            .parse()
+            .map_err(|error: Error| error.at(source, argument))
+    };
+    let bounds = |argument: &str| {
+        argument
+            .parse()
+            .map_err(|error: Error| error.within(source, argument))
+    };
+    let rule = match name {
+        "count" => {
+            arity(2)?;
+            Rule::Count(class(arguments[0])?, bounds(arguments[1])?)
+        }
+        "at" => {
+            arity(2)?;
+            let position = parse_number(source, arguments[0])?;
+            if position == 0 {
+                return Err(Error::parse("positions start at 1").at(source, arguments[0]));
+            }
+            Rule::At(position, class(arguments[1])?)
+        }
+        "length" => {
+            arity(1)?;
+            Rule::Length(bounds(arguments[0])?)
+        }
+        "forbid" => {
+            arity(1)?;
+            if arguments[0].is_empty() {
+                return Err(Error::parse("nothing to forbid").at(source, name));
+            }
+            Rule::Forbid(arguments[0].to_string())
+        }
+        "not" => {
+            arity(1)?;
+            Rule::Not(Box::new(parse_argument(source, arguments[0])?))
+        }
+        "all" | "any" | "one" => {
+            let rules = arguments
+                .iter()
+                .map(|argument| parse_argument(source, argument))
+                .collect::<Result<Vec<Rule>>>()?;
+            match name {
+                "all" => Rule::All(rules),
+                "any" => Rule::Any(rules),
+                _ => Rule::One(rules),
+            }
+        }
+        _ => return Err(Error::parse(format!("unknown rule `{}`", name)).at(source, name)),
+    };
+    Ok((rule, rest))
+}
+
+/// Parses a rule given as an argument, which nothing may follow.
+fn parse_argument(source: &str, argument: &str) -> Result<Rule> {
+    let (rule, rest) = parse_rule(source, argument)?;
+    if !rest.trim().is_empty() {
+        return Err(Error::parse(format!("unexpected `{}`", rest.trim())).at(source, rest));
+    }
+    Ok(rule)
+}
+
+/// Splits the comma-separated arguments of a rule up to its closing
+/// parenthesis, returning them trimmed along with the text after it.
+fn split_arguments<'a>(source: &str, text: &'a str) -> Result<(Vec<&'a str>, &'a str)> {
+    let mut arguments = vec![];
+    let mut depth = 0;
+    let mut start = 0;
+    for (index, ch) in text.char_indices() {
+        match ch {
+            '(' => depth += 1,
+            ')' if depth > 0 => depth -= 1,
+            ',' | ')' if depth == 0 => {
+                let argument = text[start..index].trim();
+                if !(argument.is_empty() && ch == ')' && arguments.is_empty()) {
+                    arguments.push(argument);
+                }
+                if ch == ')' {
+                    return Ok((arguments, &text[index + 1..]));
+                }
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    Err(Error::parse("expected `)`").at(source, &text[text.len()..]))
+}
+
+fn write_rules(f: &mut fmt::Formatter<'_>, name: &str, rules: &[Rule]) -> fmt::Result {
+    write!(f, "{}(", name)?;
+    for (index, rule) in rules.iter().enumerate() {
+        if index > 0 {
+            write!(f, ", ")?;
+        }
+        write!(f, "{}", rule)?;
+    }
+    write!(f, ")")
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Rule::Count(class, bounds) => write!(f, "count({}, {})", class, bounds),
+            Rule::At(position, class) => write!(f, "at({}, {})", position, class),
+            Rule::Length(bounds) => write!(f, "length({})", bounds),
+            Rule::Forbid(text) => write!(f, "forbid({})", text),
+            Rule::All(rules) => write_rules(f, "all", rules),
+            Rule::Any(rules) => write_rules(f, "any", rules),
+            Rule::One(rules) => write_rules(f, "one", rules),
+            Rule::Not(rule) => write!(f, "not({})", rule),
+        }
+    }
+}
+
+/// A line of the input: two numbers and a letter, read as a policy by each
+/// part, then the password.
 struct Policy {
     letter: char,
     first: usize,
     second: usize,
-    password: Vec<char>,
+    password: String,
 }
 
 impl Policy {
-    fn new(s: &String) -> Policy {
-        let parts: Vec<&str> = s.split(':').collect();
-        let policy: Vec<&str> = parts[0].split_ascii_whitespace().collect();
-        let letter = policy[1].chars().next().unwrap();
-        let numbers: Vec<usize> = policy[0]
-            .split('-')
-            .map(|num| num.parse().unwrap())
-            .collect();
-        Policy {
-            letter,
-            first: numbers[0],
-            second: numbers[1],
-            password: parts[1].chars().collect(),
+    fn new(s: &str) -> Result<Policy> {
+        let (policy, password) = split_pair(s, s, ":")?;
+        let (range, letter) = split_pair(s, policy, " ")?;
+        let (first, second) = split_pair(s, range, "-")?;
+        let first: usize = parse_number(s, first)?;
+        let second: usize = parse_number(s, second)?;
+        if first > second {
+            return Err(Error::parse(format!("invalid positions `{}`", range)).at(s, range));
+        }
+        let mut letters = letter.chars();
+        match (letters.next(), letters.next()) {
+            (Some(letter), None) => Ok(Policy {
+                letter,
+                first,
+                second,
+                password: password.strip_prefix(' ').unwrap_or(password).to_string(),
+            }),
+            _ => Err(
+                Error::parse(format!("expected a single letter, found `{}`", letter)).at(s, letter),
+            ),
         }
     }
 
-    fn validate_password_1(&self) -> bool {
-        let mut counter = 0;
-        for &ch in &self.password {
-            if ch == self.letter {
-                counter += 1;
-            }
+    /// The letter appears between `first` and `second` times.
+    fn rule_1(&self) -> Rule {
+        Rule::Count(
+            Class::Char(self.letter),
+            Bounds {
+                min: self.first,
+                max: Some(self.second),
+            },
+        )
+    }
+
+    /// The letter is at exactly one of the positions `first` and `second`.
+    fn rule_2(&self) -> Rule {
+        Rule::One(vec![
+            Rule::At(self.first, Class::Char(self.letter)),
+            Rule::At(self.second, Class::Char(self.letter)),
+        ])
+    }
+}
+
+fn part_1(data: &[Policy]) -> usize {
+    data.iter()
+        .filter(|policy| policy.rule_1().passes(&policy.password))
+        .count()
+}
+
+fn part_2(data: &[Policy]) -> usize {
+    data.iter()
+        .filter(|policy| policy.rule_2().passes(&policy.password))
+        .count()
+}
+
+/// The outcome of the policy of one part on a password.
+#[derive(Debug, PartialEq)]
+pub struct Outcome {
+    /// `None` for the rule given to `report_with`
+    pub part: Option<u8>,
+    pub rule: Rule,
+    pub passed: bool,
+    /// Why the password fails the policy, see `Check::reason`
+    pub reason: Option<String>,
+}
+
+/// The outcomes of the policies on the password of one line of the input.
+#[derive(Debug, PartialEq)]
+pub struct LineReport {
+    /// 1-based line of the input
+    pub line: usize,
+    pub password: String,
+    pub outcomes: Vec<Outcome>,
+}
+
+impl LineReport {
+    pub fn to_json(&self) -> String {
+        let outcomes: Vec<String> = self
+            .outcomes
+            .iter()
+            .map(|outcome| {
+                format!(
+                    "{{\"part\":{},\"rule\":{},\"passed\":{},\"reason\":{}}}",
+                    outcome
+                        .part
+                        .map_or("null".to_string(), |part| part.to_string()),
+                    json_string(&outcome.rule.to_string()),
+                    outcome.passed,
+                    outcome
+                        .reason
+                        .as_deref()
+                        .map_or("null".to_string(), json_string),
+                )
+            })
+            .collect();
+        format!(
+            "{{\"line\":{},\"password\":{},\"outcomes\":[{}]}}",
+            self.line,
+            json_string(&self.password),
+            outcomes.join(",")
+        )
+    }
+}
+
+impl Outcome {
+    fn new(part: Option<u8>, rule: Rule, password: &str) -> Outcome {
+        let check = rule.check(password);
+        let (passed, reason) = (check.passed, check.reason());
+        Outcome {
+            part,
+            rule,
+            passed,
+            reason,
         }
-        counter >= self.first && counter <= self.second
     }
+}
+
+/// Checks the password of every line of `input` against `outcomes`.
+fn report_lines<F>(input: &str, outcomes: F) -> Result<Vec<LineReport>>
+where
+    F: Fn(&Policy) -> Vec<Outcome>,
+{
+    let policies = map_lines(input, Policy::new)?;
+    Ok(policies
+        .into_iter()
+        .enumerate()
+        .map(|(index, policy)| LineReport {
+            line: index + 1,
+            outcomes: outcomes(&policy),
+            password: policy.password,
+        })
+        .collect())
+}
+
+/// Checks the password of every line of `input` against the policies of
+/// both parts, or of `part` only.
+pub fn report(input: &str, part: Option<u8>) -> Result<Vec<LineReport>> {
+    let parts: Vec<u8> = match part {
+        Some(part) => vec![part],
+        None => vec![1, 2],
+    };
+    report_lines(input, |policy| {
+        parts
+            .iter()
+            .map(|&part| {
+                let rule = if part == 1 {
+                    policy.rule_1()
+                } else {
+                    policy.rule_2()
+                };
+                Outcome::new(Some(part), rule, &policy.password)
+            })
+            .collect()
+    })
+}
 
-    fn validate_password_2(&self) -> bool {
-        self.password.len() > self.second
-            && self.password[self.first] != self.password[self.second]
-            && (self.password[self.first] == self.letter
-                || self.password[self.second] == self.letter)
+/// Checks the password of every line of `input` against `rule` instead of
+/// the policy written on the line.
+pub fn report_with(input: &str, rule: &Rule) -> Result<Vec<LineReport>> {
+    report_lines(input, |policy| {
+        vec![Outcome::new(None, rule.clone(), &policy.password)]
+    })
+}
+
+/// Lays out the reports with one row per line and one column per part,
+/// holding `pass` or the reason of the failure.
+pub fn report_table(reports: &[LineReport]) -> String {
+    let mut header = vec!["Line".to_string(), "Password".to_string()];
+    if let Some(first) = reports.first() {
+        header.extend(first.outcomes.iter().map(|outcome| match outcome.part {
+            Some(part) => format!("Part {}", part),
+            None => "Rule".to_string(),
+        }));
+    }
+    let mut rows = vec![header];
+    for report in reports {
+        let mut row = vec![report.line.to_string(), report.password.clone()];
+        row.extend(report.outcomes.iter().map(|outcome| match &outcome.reason {
+            Some(reason) => format!("fail ({})", reason),
+            None => "pass".to_string(),
+        }));
+        rows.push(row);
     }
+    table(&rows)
 }
 
-fn part_1(data: &Vec<Policy>) {
-    println!(
-        "\tPart 1: {}",
-        data.iter()
-            .filter(|policy| policy.validate_password_1())
-            .count()
-    );
+/// A random list of 1000 policies and passwords, about half of them valid.
+pub fn generate(rng: &mut Rng) -> String {
+    let mut text = String::new();
+    for _ in 0..1000 {
+        let letter = (b'a' + rng.below(26) as u8) as char;
+        let first = rng.range(1..=12);
+        let second = rng.range(first + 1..=first + 8);
+        let length = rng.range(second..=20);
+        let frequency = rng.range(1..=8) as f64 / 10.0;
+        let password: String = (0..length)
+            .map(|_| {
+                if rng.chance(frequency) {
+                    letter
+                } else {
+                    (b'a' + rng.below(26) as u8) as char
+                }
+            })
+            .collect();
+        text.push_str(&format!("{}-{} {}: {}\n", first, second, letter, password));
+    }
+    text
 }
 
-fn part_2(data: &Vec<Policy>) {
-    println!(
-        "\tPart 2: {}",
-        data.iter()
-            .filter(|policy| policy.validate_password_2())
-            .count()
-    );
+pub struct Day2 {
+    policies: Vec<Policy>,
 }
 
-pub fn main() {
-    println!("Day 2");
-    let data: Vec<Policy> = read_lines("../inputs/day2.txt")
-        .iter()
-        .map(Policy::new)
-        .collect();
-    part_1(&data);
-    part_2(&data);
+impl Solution for Day2 {
+    fn parse(input: &str) -> Result<Self> {
+        Ok(Self {
+            policies: map_lines(input, Policy::new)?,
+        })
+    }
+
+    fn part_1(&mut self) -> Result<Answer> {
+        Ok(part_1(&self.policies).into())
+    }
+
+    fn part_2(&mut self) -> Result<Answer> {
+        Ok(part_2(&self.policies).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parts() {
+        let day = Day2::parse("1-3 a: abcde\n1-3 b: cdefg\n2-9 c: ccccccccc").unwrap();
+        assert_eq!(part_1(&day.policies), 2);
+        assert_eq!(part_2(&day.policies), 1);
+    }
+
+    #[test]
+    fn test_report() {
+        let input = "1-3 a: abcde\n1-3 b: cdefg\n2-9 c: ccccccccc";
+        let reports = report(input, None).unwrap();
+        assert_eq!(reports[1].line, 2);
+        assert_eq!(reports[1].password, "cdefg");
+        let passed: Vec<Vec<bool>> = reports
+            .iter()
+            .map(|report| {
+                report
+                    .outcomes
+                    .iter()
+                    .map(|outcome| outcome.passed)
+                    .collect()
+            })
+            .collect();
+        assert_eq!(
+            passed,
+            vec![vec![true, true], vec![false, false], vec![true, false]]
+        );
+        assert_eq!(
+            reports[0].to_json(),
+            "{\"line\":1,\"password\":\"abcde\",\"outcomes\":[\
+             {\"part\":1,\"rule\":\"count(a, 1-3)\",\"passed\":true,\"reason\":null},\
+             {\"part\":2,\"rule\":\"one(at(1, a), at(3, a))\",\"passed\":true,\"reason\":null}]}"
+        );
+        assert_eq!(
+            report_table(&reports),
+            "Line  Password   Part 1                           Part 2
+1     abcde      pass                             pass
+2     cdefg      fail (count(b, 1-3): 0 matches)  fail (one(at(1, b), at(3, b)): 0 of 2 rules pass)
+3     ccccccccc  pass                             fail (one(at(2, c), at(9, c)): 2 of 2 rules pass)
+"
+        );
+        let reports = report(input, Some(2)).unwrap();
+        assert!(reports.iter().all(|report| report.outcomes.len() == 1));
+        assert_eq!(
+            report_table(&reports).lines().next(),
+            Some("Line  Password   Part 2")
+        );
+        assert_eq!(
+            report("1-3 a abcde", None).unwrap_err().to_string(),
+            "line 1, column 1: expected `:` in `1-3 a abcde`"
+        );
+        let reports = report("0-3 a: bcd", None).unwrap();
+        let passed: Vec<bool> = reports[0]
+            .outcomes
+            .iter()
+            .map(|outcome| outcome.passed)
+            .collect();
+        assert_eq!(passed, vec![true, false]);
+    }
+
+    #[test]
+    fn test_report_with() {
+        let input = "1-3 a: abcde\n1-3 b: cdefg";
+        let rule: Rule = "length(5)".parse().unwrap();
+        let reports = report_with(input, &rule).unwrap();
+        assert!(reports.iter().all(|report| report.outcomes[0].passed));
+        let rule: Rule = "forbid(de)".parse().unwrap();
+        let reports = report_with(input, &rule).unwrap();
+        assert_eq!(
+            reports[0].to_json(),
+            "{\"line\":1,\"password\":\"abcde\",\"outcomes\":[\
+             {\"part\":null,\"rule\":\"forbid(de)\",\"passed\":false,\"reason\":\"forbid(de): found at 4\"}]}"
+        );
+        assert_eq!(
+            report_table(&reports).lines().next(),
+            Some("Line  Password  Rule")
+        );
+    }
+
+    #[test]
+    fn test_parse_rule() {
+        for source in [
+            "count(a, 1-3)",
+            "at(2, digit)",
+            "length(8-)",
+            "forbid(abc)",
+            "all(count(upper, 1-), not(any(forbid(123), length(0-7))), one(at(1, x), at(4, x)))",
+            "all()",
+        ] {
+            assert_eq!(source.parse::<Rule>().unwrap().to_string(), source);
+        }
+        assert_eq!(
+            " all ( count( a ,2 ) )".parse::<Rule>().unwrap(),
+            Rule::All(vec![Rule::Count(
+                Class::Char('a'),
+                Bounds {
+                    min: 2,
+                    max: Some(2)
+                }
+            )])
+        );
+        let error = |source: &str| source.parse::<Rule>().unwrap_err().to_string();
+        assert_eq!(error("count(a, 3-1)"), "empty range `3-1`");
+        assert_eq!(
+            error("count(ab, 1)"),
+            "expected a character or a class, found `ab`"
+        );
+        assert_eq!(error("at(0, a)"), "positions start at 1");
+        assert_eq!(
+            error("not(length(1), length(2))"),
+            "`not` takes 1 argument, found 2"
+        );
+        assert_eq!(error("size(3)"), "unknown rule `size`");
+        assert_eq!(error("all(length(1)"), "expected `)`");
+        assert_eq!(error("length(1) x"), "unexpected `x`");
+    }
+
+    #[test]
+    fn test_check() {
+        let rule: Rule = "all(count(a, 1-3), one(at(1, a), at(3, a)), not(forbid(zz)))"
+            .parse()
+            .unwrap();
+        for password in ["abcde", "cdefg", "ccccccccc", "aaaa", "zz", "abazz", ""] {
+            let check = rule.check(password);
+            assert_eq!(check.reason().is_none(), check.passed, "{}", password);
+        }
+        assert!(rule.passes("abczz"));
+        assert!(!rule.passes("cdefg"));
+        assert_eq!(
+            "any()"
+                .parse::<Rule>()
+                .unwrap()
+                .check("abcde")
+                .reason()
+                .unwrap(),
+            "any(): 0 of 0 rules pass"
+        );
+        assert_eq!(rule.check("abcde").found, "2 of 3 rules pass");
+        assert_eq!(
+            rule.check("aaaa").reason().unwrap(),
+            "count(a, 1-3): 4 matches; one(at(1, a), at(3, a)): 2 of 2 rules pass; \
+             not(forbid(zz)): passes"
+        );
+        assert_eq!(
+            "at(6, a)"
+                .parse::<Rule>()
+                .unwrap()
+                .check("abcde")
+                .reason()
+                .unwrap(),
+            "at(6, a): no character"
+        );
+        assert_eq!(
+            "forbid(cd)"
+                .parse::<Rule>()
+                .unwrap()
+                .check("abcde")
+                .reason()
+                .unwrap(),
+            "forbid(cd): found at 3"
+        );
+    }
 }
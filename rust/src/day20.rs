@@ -3,7 +3,12 @@ use std::{
     collections::{HashMap, HashSet},
 };
 
-use crate::utilities::read_block;
+use crate::error::{Error, Result};
+use crate::generate::Rng;
+use crate::grid::{Grid, Position};
+use crate::solution::{Answer, Solution};
+use crate::utilities::{map_blocks, parse_number, split_first_line};
+
 const MONSTER_OFFSETS: [(usize, usize); 15] = [
     (1, 0),
     (2, 1),
@@ -30,27 +35,37 @@ enum Border {
 }
 
 impl Border {
-    fn get_border(&self, tile: &Vec<String>) -> String {
+    fn get_border(&self, tile: &Grid<char>) -> String {
         match self {
-            Border::Top => tile[0].clone(),
-            Border::Bottom => tile.last().unwrap().clone(),
-            Border::Left => tile.iter().map(|s| s.chars().next().unwrap()).collect(),
-            Border::Right => tile.iter().map(|s| s.chars().last().unwrap()).collect(),
+            Border::Top => tile.row(0).iter().collect(),
+            Border::Bottom => tile.row(tile.height() - 1).iter().collect(),
+            Border::Left => tile.column(0).collect(),
+            Border::Right => tile.column(tile.width() - 1).collect(),
         }
     }
 }
 struct Tile {
-    tile: Vec<String>,
+    tile: Grid<char>,
     borders: HashSet<String>,
     id: u64,
 }
 
 impl Tile {
-    fn new(tile_info: String) -> Self {
-        let mut iter = tile_info.lines();
-        let header = iter.next().unwrap();
-        let id = header[5..header.len() - 1].parse().unwrap();
-        let tile: Vec<String> = iter.map(|line| line.to_string()).collect();
+    fn new(tile_info: &str) -> Result<Self> {
+        let (header, image) = split_first_line(tile_info)
+            .ok_or_else(|| Error::parse("expected a tile below its header").on_line(1))?;
+        let id = header
+            .strip_prefix("Tile ")
+            .and_then(|id| id.strip_suffix(':'))
+            .ok_or_else(|| Error::parse(format!("invalid tile header `{}`", header)).on_line(1))?;
+        let id = parse_number(header, id).map_err(|error| error.on_line(1))?;
+        let tile = Grid::parse(image, &['.', '#']).map_err(|error| error.shift_lines(1))?;
+        if tile.width() != tile.height() || tile.height() < 3 {
+            return Err(
+                Error::parse(format!("tile {} is not a square of at least 3 pixels", id))
+                    .on_line(2),
+            );
+        }
         let mut borders: HashSet<String> = HashSet::new();
         let top = Border::Top.get_border(&tile);
         borders.insert(top.chars().rev().collect());
@@ -64,21 +79,16 @@ impl Tile {
         let right: String = Border::Right.get_border(&tile);
         borders.insert(right.chars().rev().collect());
         borders.insert(right);
-        Self { id, tile, borders }
+        Ok(Self { id, tile, borders })
     }
 
     fn is_neighbor(&self, other: &Tile) -> bool {
         self.id != other.id && self.borders.intersection(&other.borders).count() > 0
     }
 
-    fn without_borders(&self) -> Vec<Vec<char>> {
-        let len = self.tile.len();
-        self.tile
-            .iter()
-            .skip(1)
-            .map(|line| line.chars().skip(1).take(len - 2).collect::<Vec<char>>())
-            .take(len - 2)
-            .collect()
+    fn without_borders(&self) -> Grid<char> {
+        let len = self.tile.height();
+        self.tile.sub_grid((1, 1), len - 2, len - 2)
     }
 
     fn match_border(&self, other: &Tile, border: Border) -> bool {
@@ -86,25 +96,11 @@ impl Tile {
     }
 
     fn flip(&mut self) {
-        self.tile = self.tile.iter().rev().map(|s| s.clone()).collect();
+        self.tile = self.tile.flip();
     }
 
     fn rotate(&mut self) {
-        let tile: Vec<Vec<char>> = self
-            .tile
-            .iter()
-            .map(|s| s.chars().collect::<Vec<char>>())
-            .collect();
-        // let mut new_tile = vec![vec!['.'; tile.len()]; tile.len()];
-        // for i in 0..tile.len() {
-        //     for j in 0..tile.len() {
-        //         new_tile[i][j] = tile[j][tile.len() - 1 - i];
-        //     }
-        // }
-        self.tile = rotate(tile)
-            .into_iter()
-            .map(|s| s.into_iter().collect())
-            .collect();
+        self.tile = self.tile.rotate();
     }
 
     fn is_top_left(&self, neighbor_1: &Tile, neighbor_2: &Tile) -> bool {
@@ -133,10 +129,10 @@ impl Tile {
         false
     }
 
-    fn adapt(&mut self, target_border: String, border: Border) {
+    fn adapt(&mut self, target_border: String, border: Border) -> Result<()> {
         for _ in 0..4 {
             if border.get_border(&self.tile) == target_border {
-                return;
+                return Ok(());
             }
             // Rotate to adapt to the position
             self.rotate();
@@ -144,25 +140,36 @@ impl Tile {
         self.flip();
         for _ in 0..4 {
             if border.get_border(&self.tile) == target_border {
-                return;
+                return Ok(());
             }
             // Rotate to adapt to the position
             self.rotate();
         }
-
-        unreachable!()
+        Err(Error::unsolvable(format!(
+            "tile {} has no border matching its neighbor",
+            self.id
+        )))
     }
 }
 
-struct Solution {
+pub struct Day20 {
     tiles: HashMap<u64, RefCell<Tile>>,
     neighbors: HashMap<u64, Vec<u64>>,
     cornors: Vec<u64>,
 }
 
-impl Solution {
-    fn new(tiles: Vec<String>) -> Self {
-        let tiles_vec: Vec<Tile> = tiles.into_iter().map(Tile::new).collect();
+impl Day20 {
+    fn new(input: &str) -> Result<Self> {
+        let tiles_vec: Vec<Tile> = map_blocks(input, Tile::new)?;
+        if let Some(tile) = tiles_vec
+            .iter()
+            .find(|tile| tile.tile.height() != tiles_vec[0].tile.height())
+        {
+            return Err(Error::parse(format!(
+                "tile {} has a different size than tile {}",
+                tile.id, tiles_vec[0].id
+            )));
+        }
         let mut tiles = HashMap::new();
         let mut neighbors: HashMap<u64, Vec<u64>> = HashMap::new();
 
@@ -178,63 +185,92 @@ impl Solution {
         }
 
         for tile in tiles_vec {
-            tiles.insert(tile.id, RefCell::new(tile));
+            if let Some(tile) = tiles.insert(tile.id, RefCell::new(tile)) {
+                return Err(Error::parse(format!(
+                    "tile {} appears more than once",
+                    tile.borrow().id
+                )));
+            }
         }
-        Self {
+        Ok(Self {
             tiles,
             neighbors,
             cornors: Vec::new(),
+        })
+    }
+
+    fn solve_part_1(&mut self) -> Result<u64> {
+        self.cornors = self
+            .neighbors
+            .iter()
+            .filter(|(_, neighbors)| neighbors.len() == 2)
+            .map(|(&id, _)| id)
+            .collect();
+        if self.cornors.len() != 4 {
+            return Err(Error::unsolvable(format!(
+                "expected 4 corner tiles, found {}",
+                self.cornors.len()
+            )));
         }
+        self.cornors
+            .iter()
+            .try_fold(1u64, |answer, &id| answer.checked_mul(id))
+            .ok_or_else(|| Error::unsolvable("the product of the corner ids overflows 64 bits"))
     }
 
-    fn solve_part_1(&mut self) -> u64 {
-        let mut answer = 1;
-        for (&id, neighbors) in self.neighbors.iter() {
-            if neighbors.len() == 2 {
-                self.cornors.push(id);
-                answer *= id;
-            }
+    fn assemble(&mut self) -> Result<Grid<char>> {
+        if self.cornors.is_empty() {
+            // The corners are found while solving part 1
+            self.solve_part_1()?;
         }
-        answer
+        self.construct_image()?
+            .ok_or_else(|| Error::unsolvable("the tiles cannot be assembled into a square image"))
     }
 
-    fn solve_part_2(&mut self) -> usize {
-        let image = self.construct_image();
-        let total_hash: usize = image
-            .iter()
-            .map(|row| row.iter().filter(|pixel| **pixel == '#').count())
-            .sum();
+    fn solve_part_2(&mut self) -> Result<usize> {
+        let image = self.assemble()?;
+        let total_hash = image.iter().filter(|pixel| **pixel == '#').count();
 
-        total_hash - 15 * count_monsters(image)
+        let monsters =
+            count_monsters(image).ok_or_else(|| Error::unsolvable("no sea monster is found"))?;
+        Ok(total_hash - 15 * monsters)
     }
 
-    fn construct_image(&mut self) -> Vec<Vec<char>> {
+    /// Returns `None` unless every tile fits into a square image.
+    fn construct_image(&mut self) -> Result<Option<Grid<char>>> {
         let mut current_id = self.find_top_left();
         let mut tile_image = vec![];
+        let mut placed = 0;
         while current_id.is_some() {
-            let (new_current_id, row) = self.construct_row(current_id);
+            let (new_current_id, row) = match self.construct_row(current_id)? {
+                Some(row) => row,
+                None => return Ok(None),
+            };
+            placed += row.len();
+            if placed > self.tiles.len() {
+                return Ok(None);
+            }
             tile_image.push(row);
             current_id = new_current_id;
         }
-
-        let mut image = vec![];
-        for row in tile_image {
-            let tiles_without_border: Vec<Vec<Vec<char>>> = row
-                .into_iter()
-                .map(|id| self.tiles.get(&id).unwrap().borrow().without_borders())
-                .collect();
-            let mut tmp = vec![];
-            let len = tiles_without_border[0].len();
-            for i in 0..len {
-                let mut new_row: Vec<char> = vec![];
-                for tile in &tiles_without_border {
-                    new_row.extend(tile[i].iter());
-                }
-                tmp.push(new_row);
-            }
-            image.extend(tmp.into_iter());
+        if placed != self.tiles.len() || tile_image.iter().any(|row| row.len() != tile_image.len())
+        {
+            return Ok(None);
         }
-        image
+
+        let tiles: Vec<Vec<Grid<char>>> = tile_image
+            .into_iter()
+            .map(|row| {
+                row.into_iter()
+                    .map(|id| self.tiles.get(&id).unwrap().borrow().without_borders())
+                    .collect()
+            })
+            .collect();
+        let len = tiles[0][0].height();
+        let size = len * tiles.len();
+        Ok(Some(Grid::from_fn(size, size, |(row, col)| {
+            tiles[row / len][col / len][(row % len, col % len)]
+        })))
     }
 
     fn find_top_left(&self) -> Option<u64> {
@@ -250,15 +286,22 @@ impl Solution {
         None
     }
 
-    fn construct_row(&self, mut current_id: Option<u64>) -> (Option<u64>, Vec<u64>) {
+    /// Returns `None` when the row does not end before using every tile.
+    fn construct_row(
+        &self,
+        mut current_id: Option<u64>,
+    ) -> Result<Option<(Option<u64>, Vec<u64>)>> {
         let mut row = vec![];
         while let Some(id) = current_id {
+            if row.len() == self.tiles.len() {
+                return Ok(None);
+            }
             row.push(id);
-            current_id = self.connect_borders(id, Border::Right, Border::Left);
+            current_id = self.connect_borders(id, Border::Right, Border::Left)?;
         }
         // Get id of the first tile of the next row
-        current_id = self.connect_borders(row[0], Border::Bottom, Border::Top);
-        (current_id, row)
+        current_id = self.connect_borders(row[0], Border::Bottom, Border::Top)?;
+        Ok(Some((current_id, row)))
     }
 
     fn connect_borders(
@@ -266,81 +309,194 @@ impl Solution {
         current_id: u64,
         match_border: Border,
         adapt_border: Border,
-    ) -> Option<u64> {
+    ) -> Result<Option<u64>> {
         let tile = self.tiles.get(&current_id).unwrap().borrow();
         for neighbor_id in self.neighbors.get(&current_id).unwrap() {
             let mut neighbor = self.tiles.get(neighbor_id).unwrap().borrow_mut();
             if tile.match_border(&neighbor, match_border) {
-                neighbor.adapt(match_border.get_border(&tile.tile), adapt_border);
-                return Some(*neighbor_id);
+                neighbor.adapt(match_border.get_border(&tile.tile), adapt_border)?;
+                return Ok(Some(*neighbor_id));
             }
         }
-        None
+        Ok(None)
     }
 }
 
-fn flip(image: Vec<Vec<char>>) -> Vec<Vec<char>> {
-    image.into_iter().rev().collect()
+fn is_monster_here(image: &Grid<char>, row: usize, col: usize) -> bool {
+    MONSTER_OFFSETS
+        .iter()
+        .all(|offset| image.get((row + offset.0, col + offset.1)) == Some(&'#'))
 }
 
-fn rotate(image: Vec<Vec<char>>) -> Vec<Vec<char>> {
-    let n = image.len();
-    let mut new_image = vec![vec!['.'; n]; n];
+/// The top left corners of the sea monsters of the image, as it is.
+fn find_monsters(image: &Grid<char>) -> Vec<Position> {
+    image
+        .positions()
+        .filter(|&(row, col)| is_monster_here(image, row, col))
+        .collect()
+}
+
+/// A pixel of the image being assembled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Pixel {
+    /// Part of a tile not placed yet
+    Missing,
+    Water,
+    Rough,
+    Monster,
+}
 
-    for i in 0..n {
-        for j in 0..n {
-            new_image[i][j] = image[j][n - 1 - i];
+/// The image after placing each tile, row after row, turned to show the sea
+/// monsters, and lastly with the monsters highlighted.
+pub fn assembly(input: &str) -> Result<Vec<Grid<Pixel>>> {
+    let mut day = Day20::new(input)?;
+    let image = day.assemble()?;
+    let len = day.tiles.values().next().unwrap().borrow().tile.height() - 2;
+    let side = image.width() / len;
+    let order = Grid::from_fn(image.width(), image.height(), |(row, col)| {
+        row / len * side + col / len
+    });
+
+    let orientations = image.orientations();
+    let (index, monsters) = orientations
+        .iter()
+        .map(find_monsters)
+        .enumerate()
+        .find(|(_, monsters)| !monsters.is_empty())
+        .ok_or_else(|| Error::unsolvable("no sea monster is found"))?;
+    let order = &order.orientations()[index];
+    let mut pixels = orientations[index].map(|&pixel| match pixel {
+        '#' => Pixel::Rough,
+        _ => Pixel::Water,
+    });
+    let mut frames: Vec<Grid<Pixel>> = (1..=side * side)
+        .map(|placed| {
+            Grid::from_fn(pixels.width(), pixels.height(), |position| {
+                if order[position] < placed {
+                    pixels[position]
+                } else {
+                    Pixel::Missing
+                }
+            })
+        })
+        .collect();
+    for (row, col) in monsters {
+        for offset in &MONSTER_OFFSETS {
+            pixels[(row + offset.0, col + offset.1)] = Pixel::Monster;
         }
     }
-    new_image
+    frames.push(pixels);
+    Ok(frames)
 }
 
-fn is_monster_here(image: &Vec<Vec<char>>, row: usize, col: usize) -> bool {
-    MONSTER_OFFSETS.iter().all(|offset| {
-        let row = row + offset.0;
-        let col = col + offset.1;
-        row < image.len() && col < image[row].len() && image[row][col] == '#'
-    })
+fn count_monsters(image: Grid<char>) -> Option<usize> {
+    image
+        .orientations()
+        .iter()
+        .map(|image| find_monsters(image).len())
+        .find(|&monsters| monsters > 0)
 }
 
-fn count_monsters(mut image: Vec<Vec<char>>) -> usize {
-    let mut monsters = 0;
-    for _ in 0..4 {
-        for row in 0..image.len() {
-            for col in 0..image[0].len() {
-                if is_monster_here(&image, row, col) {
-                    monsters += 1;
-                }
+/// A random square image of 8 to 12 tiles a side, with sea monsters, cut
+/// into tiles of 10 pixels turned and shuffled at random. Every border is
+/// different, so that only neighbours share one.
+pub fn generate(rng: &mut Rng) -> String {
+    let side = rng.range(8..=12) as usize;
+    let size = side * 8;
+    let image = loop {
+        let mut image = Grid::from_fn(size, size, |_| rng.chance(0.3));
+        let mut taken = Grid::new(size, size, false);
+        let mut planted = 0;
+        for attempt in 0..side * side / 4 {
+            let row = rng.below(size - 2);
+            let col = rng.below(size - 19);
+            let area = (row..row + 3).flat_map(|row| (col..col + 20).map(move |col| (row, col)));
+            if attempt > 0 && area.clone().any(|position| taken[position]) {
+                continue;
+            }
+            for position in area {
+                taken[position] = true;
             }
+            for offset in &MONSTER_OFFSETS {
+                image[(row + offset.0, col + offset.1)] = true;
+            }
+            planted += 1;
+        }
+        // A stray monster, turned another way or overlapping the planted
+        // ones, would make the answer depend on the orientation found first
+        let pixels = image.map(|&pixel| if pixel { '#' } else { '.' });
+        let found: Vec<usize> = pixels
+            .orientations()
+            .iter()
+            .map(|pixels| find_monsters(pixels).len())
+            .collect();
+        if found[0] == planted && found[1..].iter().all(|&count| count == 0) {
+            break image;
         }
-        if monsters > 0 {
-            return monsters;
+    };
+
+    // The tiles overlap on their borders, every 9 pixels
+    let mut picture = Grid::from_fn(side * 9 + 1, side * 9 + 1, |(row, col)| {
+        if row % 9 == 0 || col % 9 == 0 {
+            rng.chance(0.5)
+        } else {
+            image[(row / 9 * 8 + row % 9 - 1, col / 9 * 8 + col % 9 - 1)]
         }
-        image = rotate(image);
-    }
-    image = flip(image);
-    for _ in 0..4 {
-        for row in 0..image.len() {
-            for col in 0..image[0].len() {
-                if is_monster_here(&image, row, col) {
-                    monsters += 1;
+    });
+    let mut borders = HashSet::new();
+    for horizontal in [true, false] {
+        for line in 0..=side {
+            for tile in 0..side {
+                let position = |index: usize| {
+                    if horizontal {
+                        (line * 9, tile * 9 + index)
+                    } else {
+                        (tile * 9 + index, line * 9)
+                    }
+                };
+                loop {
+                    let border: Vec<bool> = (0..10).map(|index| picture[position(index)]).collect();
+                    let reversed: Vec<bool> = border.iter().rev().copied().collect();
+                    // Palindromes would let a tile fit both ways round
+                    if border != reversed && borders.insert(border.min(reversed)) {
+                        break;
+                    }
+                    for index in 1..9 {
+                        picture[position(index)] = rng.chance(0.5);
+                    }
                 }
             }
         }
-        if monsters > 0 {
-            return monsters;
-        }
-        image = rotate(image);
     }
-    unreachable!()
+
+    let mut ids: Vec<usize> = (1000..10000).collect();
+    rng.shuffle(&mut ids);
+    let mut tiles: Vec<String> = (0..side * side)
+        .map(|index| {
+            let corner = (index / side * 9, index % side * 9);
+            let tile = picture
+                .sub_grid(corner, 10, 10)
+                .map(|&pixel| if pixel { '#' } else { '.' });
+            let tile = &tile.orientations()[rng.below(8)];
+            format!("Tile {}:\n{}\n", ids[index], tile)
+        })
+        .collect();
+    rng.shuffle(&mut tiles);
+    tiles.join("\n")
 }
 
-pub fn main() {
-    println!("Day 20");
-    let tiles = read_block("../inputs/day20.txt");
-    let mut solution = Solution::new(tiles);
-    println!("\tPart1: {}", solution.solve_part_1());
-    println!("\tPart2: {}", solution.solve_part_2());
+impl Solution for Day20 {
+    fn parse(input: &str) -> Result<Self> {
+        Self::new(input)
+    }
+
+    fn part_1(&mut self) -> Result<Answer> {
+        Ok(self.solve_part_1()?.into())
+    }
+
+    fn part_2(&mut self) -> Result<Answer> {
+        Ok(self.solve_part_2()?.into())
+    }
 }
 
 #[cfg(test)]
@@ -456,8 +612,21 @@ Tile 3079:
 ..#.###...
 ..#.......
 ..#.###..."#;
-        let mut solution = Solution::new(test_input.split("\n\n").map(|s| s.to_string()).collect());
-        assert_eq!(solution.solve_part_1(), 20899048083289);
-        assert_eq!(solution.solve_part_2(), 273);
+        let mut solution = Day20::new(test_input).unwrap();
+        assert_eq!(solution.solve_part_1().unwrap(), 20899048083289);
+        assert_eq!(solution.solve_part_2().unwrap(), 273);
+    }
+
+    #[test]
+    fn test_adapt() {
+        let mut tile = Tile::new("Tile 7:\n#..\n...\n...").unwrap();
+        assert!(tile.adapt("..#".to_string(), Border::Right).is_ok());
+        assert_eq!(Border::Right.get_border(&tile.tile), "..#");
+        assert_eq!(
+            tile.adapt("###".to_string(), Border::Top)
+                .unwrap_err()
+                .to_string(),
+            "no solution: tile 7 has no border matching its neighbor"
+        );
     }
 }
@@ -1,44 +1,55 @@
 use std::collections::{HashMap, HashSet};
 
-use crate::utilities::read_lines;
+use crate::error::{Error, Result};
+use crate::generate::Rng;
+use crate::solution::{Answer, Solution};
+use crate::utilities::map_lines;
 
-struct Solution {
+type Food = (HashSet<String>, HashSet<String>);
+
+/// Reads the ingredients and the allergens of a food, which has no allergen
+/// listed when the parenthesis is left out.
+fn parse_food(line: &str) -> Result<Food> {
+    let (ingredients, allergens) = match line.split_once(" (") {
+        Some((ingredients, allergens)) => {
+            let allergens = allergens
+                .strip_prefix("contains ")
+                .and_then(|allergens| allergens.strip_suffix(')'))
+                .ok_or_else(|| {
+                    Error::parse("expected `(contains ...)` after the ingredients")
+                        .at(line, allergens)
+                })?;
+            (ingredients, allergens.split(", ").collect())
+        }
+        None => (line, HashSet::new()),
+    };
+    if ingredients.is_empty() {
+        return Err(Error::parse("the food has no ingredient"));
+    }
+    Ok((
+        ingredients.split(' ').map(str::to_string).collect(),
+        allergens.into_iter().map(str::to_string).collect(),
+    ))
+}
+
+pub struct Day21 {
     ingredients: HashMap<String, HashSet<String>>,
     allergens: HashMap<String, HashSet<String>>,
     ingredient_counts: HashMap<String, i32>,
     total_ingredients: i32,
 }
 
-impl Solution {
-    fn new(foods: Vec<String>) -> Self {
+impl Day21 {
+    fn new(foods: &str) -> Result<Self> {
         let mut ingredients: HashMap<String, HashSet<String>> = HashMap::new();
         let mut allergens: HashMap<String, HashSet<String>> = HashMap::new();
         let mut ingredient_counts = HashMap::new();
         let mut total_ingredients = 0;
-        for food in foods {
-            let mut iter = food.split(" (");
-            let food_ingredients: HashSet<String> = iter
-                .next()
-                .unwrap()
-                .split(' ')
-                .map(|ingredient| ingredient.to_string())
-                .collect();
-            let food_allergens: HashSet<String> = iter
-                .next()
-                .unwrap()
-                .replace(")", "")
-                .replace("contains ", "")
-                .split(", ")
-                .map(|allergen| allergen.to_string())
-                .collect();
-
+        for (food_ingredients, food_allergens) in map_lines(foods, parse_food)? {
             for ingredient in &food_ingredients {
                 match ingredients.get_mut(ingredient) {
                     Some(allergens) => {
-                        *allergens = allergens
-                            .union(&food_allergens)
-                            .map(|s| s.clone())
-                            .collect();
+                        *allergens = allergens.union(&food_allergens).cloned().collect();
                     }
                     None => {
                         ingredients.insert(ingredient.clone(), food_allergens.clone());
@@ -53,7 +64,7 @@ impl Solution {
                     Some(ingredients) => {
                         *ingredients = ingredients
                             .intersection(&food_ingredients)
-                            .map(|s| s.clone())
+                            .cloned()
                             .collect();
                     }
                     None => {
@@ -62,15 +73,15 @@ impl Solution {
                 }
             }
         }
-        Self {
+        Ok(Self {
             ingredients,
             allergens,
             ingredient_counts,
             total_ingredients,
-        }
+        })
     }
 
-    fn solve_part_1(&mut self) -> i32 {
+    fn identify_allergens(&mut self) {
         let mut seen = HashSet::new();
         let mut stack = vec![];
         loop {
@@ -103,6 +114,10 @@ impl Solution {
                 }
             }
         }
+    }
+
+    fn solve_part_1(&mut self) -> i32 {
+        self.identify_allergens();
         let mut valid_ingredients_count = 0;
         for ingredients in self.allergens.values() {
             for ingredient in ingredients {
@@ -112,7 +127,18 @@ impl Solution {
         self.total_ingredients - valid_ingredients_count
     }
 
-    fn solve_part_2(&self) -> String {
+    fn solve_part_2(&mut self) -> Result<String> {
+        self.identify_allergens();
+        if let Some((allergen, _)) = self
+            .allergens
+            .iter()
+            .find(|(_, ingredients)| ingredients.len() != 1)
+        {
+            return Err(Error::unsolvable(format!(
+                "the ingredient containing {} cannot be identified",
+                allergen
+            )));
+        }
         let mut pairs = vec![];
         for (allergen, ingredients) in &self.allergens {
             for ingredient in ingredients {
@@ -120,21 +146,108 @@ impl Solution {
             }
         }
         pairs.sort();
-        pairs
+        Ok(pairs
             .into_iter()
             .map(|pair| pair.1.to_string())
             .collect::<Vec<String>>()
-            .join(",")
-            .to_string()
+            .join(","))
+    }
+}
+
+const ALLERGENS: [&str; 9] = [
+    "dairy",
+    "eggs",
+    "fish",
+    "nuts",
+    "peanuts",
+    "sesame",
+    "shellfish",
+    "soy",
+    "wheat",
+];
+
+/// Random foods made of 200 ingredients, 8 of them holding an allergen
+/// each. The foods list some of their allergens.
+fn random_foods(rng: &mut Rng) -> (String, String) {
+    let mut allergens = ALLERGENS.to_vec();
+    rng.shuffle(&mut allergens);
+    allergens.truncate(8);
+    allergens.sort_unstable();
+    let mut ingredients = HashSet::new();
+    while ingredients.len() < 200 {
+        let len = rng.range(4..=8);
+        ingredients.insert(
+            (0..len)
+                .map(|_| (b'a' + rng.below(26) as u8) as char)
+                .collect::<String>(),
+        );
     }
+    // Sorted first, as the order of a set changes from run to run
+    let mut ingredients: Vec<String> = ingredients.into_iter().collect();
+    ingredients.sort_unstable();
+    rng.shuffle(&mut ingredients);
+    // The first ingredients hold the allergens, in order
+    let (dangerous, safe) = ingredients.split_at_mut(allergens.len());
+
+    let mut foods = String::new();
+    for _ in 0..rng.range(35..=45) {
+        rng.shuffle(safe);
+        let mut food: Vec<&str> = safe[..rng.range(30..=80) as usize]
+            .iter()
+            .map(String::as_str)
+            .collect();
+        let mut listed = vec![];
+        for (allergen, ingredient) in allergens.iter().zip(dangerous.iter()) {
+            if rng.chance(0.4) {
+                food.push(ingredient);
+                if rng.chance(0.6) {
+                    listed.push(*allergen);
+                }
+            }
+        }
+        if listed.is_empty() {
+            let index = rng.below(allergens.len());
+            food.push(&dangerous[index]);
+            listed.push(allergens[index]);
+        }
+        food.sort_unstable();
+        food.dedup();
+        rng.shuffle(&mut food);
+        foods.push_str(&format!(
+            "{} (contains {})\n",
+            food.join(" "),
+            listed.join(", ")
+        ));
+    }
+    (foods, dangerous.join(","))
 }
 
-pub fn main() {
-    println!("Day 21");
-    let foods = read_lines("../inputs/day21.txt");
-    let mut solution = Solution::new(foods);
-    println!("\tPart1: {}", solution.solve_part_1());
-    println!("\tPart2: {}", solution.solve_part_2());
+/// Random foods whose allergens can be told apart.
+pub fn generate(rng: &mut Rng) -> String {
+    loop {
+        let (foods, dangerous) = random_foods(rng);
+        if let Ok(mut day) = Day21::new(&foods) {
+            if let Ok(list) = day.solve_part_2() {
+                if list == dangerous {
+                    return foods;
+                }
+            }
+        }
+    }
+}
+
+impl Solution for Day21 {
+    fn parse(input: &str) -> Result<Self> {
+        Self::new(input)
+    }
+
+    fn part_1(&mut self) -> Result<Answer> {
+        Ok(self.solve_part_1().into())
+    }
+
+    fn part_2(&mut self) -> Result<Answer> {
+        Ok(self.solve_part_2()?.into())
+    }
 }
 
 #[cfg(test)]
@@ -147,8 +260,11 @@ mod tests {
 trh fvjkl sbzzf mxmxvkd (contains dairy)
 sqjhc fvjkl (contains soy)
 sqjhc mxmxvkd sbzzf (contains fish)"#;
-        let mut solution = Solution::new(test_input.lines().map(str::to_string).collect());
+        let mut solution = Day21::new(test_input).unwrap();
         assert_eq!(solution.solve_part_1(), 5);
-        assert_eq!(solution.solve_part_2(), "mxmxvkd,sqjhc,fvjkl".to_string());
+        assert_eq!(
+            solution.solve_part_2().unwrap(),
+            "mxmxvkd,sqjhc,fvjkl".to_string()
+        );
     }
 }
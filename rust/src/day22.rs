@@ -2,7 +2,10 @@ use std::collections::hash_map::DefaultHasher;
 use std::collections::{HashSet, VecDeque};
 use std::hash::{Hash, Hasher};
 
-use crate::utilities::read_block;
+use crate::error::{Error, Result};
+use crate::generate::Rng;
+use crate::solution::{Answer, Solution};
+use crate::utilities::{map_blocks, map_lines, parse_number, split_first_line};
 
 #[derive(Debug, Clone)]
 struct Player {
@@ -10,24 +13,42 @@ struct Player {
 }
 
 impl Player {
-    fn new(player: &String) -> Self {
-        Self {
-            deck: player
-                .lines()
-                .skip(1)
-                .map(|card| card.parse().unwrap())
-                .collect(),
+    fn new(player: &str) -> Result<Self> {
+        let (header, deck) = split_first_line(player)
+            .ok_or_else(|| Error::parse("expected a deck below the player").on_line(1))?;
+        if !(header.starts_with("Player ") && header.ends_with(':')) {
+            return Err(Error::parse(format!("invalid player header `{}`", header)).on_line(1));
         }
+        let deck = map_lines(deck, |card| match parse_number(card, card)? {
+            0 => Err(Error::parse("cards start from 1")),
+            card => Ok(card),
+        })
+        .map_err(|error| error.shift_lines(1))?;
+        Ok(Self {
+            deck: deck.into_iter().collect(),
+        })
     }
 
-    fn combat(&mut self, other: &mut Player) -> bool {
+    /// Plain combat has no rule for repeated rounds, so a repeat means the
+    /// game never ends.
+    fn combat(&mut self, other: &mut Player) -> Result<bool> {
+        let mut memory = HashSet::new();
         while !(self.is_lost() || other.is_lost()) {
+            if !memory.insert(self.game_state(other)) {
+                return Err(Error::unsolvable("the game repeats forever"));
+            }
             let self_card = self.draw().unwrap();
             let other_card = other.draw().unwrap();
             let self_win = self_card > other_card;
             self.collect_cards(other, self_win, self_card, other_card);
         }
-        other.is_lost()
+        Ok(other.is_lost())
+    }
+
+    fn game_state(&self, other: &Player) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        (&self.deck, &other.deck).hash(&mut hasher);
+        hasher.finish()
     }
 
     fn recursive_combat(&mut self, other: &mut Player) -> bool {
@@ -43,12 +64,7 @@ impl Player {
         }
         let mut memory = HashSet::new();
         while !(self.is_lost() || other.is_lost()) {
-            let mut hasher = DefaultHasher::new();
-
-            (&self.deck, &other.deck).hash(&mut hasher);
-            let game_state = hasher.finish();
-
-            if !memory.insert(game_state) {
+            if !memory.insert(self.game_state(other)) {
                 // Found the same game state
                 return true;
             }
@@ -116,19 +132,19 @@ impl Player {
     }
 }
 
-fn solve_part_1(players: &Vec<String>) -> usize {
-    let mut player1 = Player::new(&players[0]);
-    let mut player2 = Player::new(&players[1]);
-    if player1.combat(&mut player2) {
+fn solve_part_1(players: &[Player]) -> Result<usize> {
+    let mut player1 = players[0].clone();
+    let mut player2 = players[1].clone();
+    Ok(if player1.combat(&mut player2)? {
         player1.score()
     } else {
         player2.score()
-    }
+    })
 }
 
-fn solve_part_2(players: &Vec<String>) -> usize {
-    let mut player1 = Player::new(&players[0]);
-    let mut player2 = Player::new(&players[1]);
+fn solve_part_2(players: &[Player]) -> usize {
+    let mut player1 = players[0].clone();
+    let mut player2 = players[1].clone();
     if player1.recursive_combat(&mut player2) {
         player1.score()
     } else {
@@ -136,11 +152,48 @@ fn solve_part_2(players: &Vec<String>) -> usize {
     }
 }
 
-pub fn main() {
-    println!("Day 22");
-    let players = read_block("../inputs/day22.txt");
-    println!("\tPart1: {}", solve_part_1(&players));
-    println!("\tPart2: {}", solve_part_2(&players));
+/// The 50 cards dealt at random between both players.
+pub fn generate(rng: &mut Rng) -> String {
+    let mut cards: Vec<String> = (1..=50).map(|card: usize| card.to_string()).collect();
+    rng.shuffle(&mut cards);
+    format!(
+        "Player 1:\n{}\n\nPlayer 2:\n{}\n",
+        cards[..25].join("\n"),
+        cards[25..].join("\n")
+    )
+}
+
+pub struct Day22 {
+    players: Vec<Player>,
+}
+
+impl Solution for Day22 {
+    fn parse(input: &str) -> Result<Self> {
+        let players = map_blocks(input, Player::new)?;
+        if players.len() != 2 {
+            return Err(Error::parse(format!(
+                "expected 2 players, found {}",
+                players.len()
+            )));
+        }
+        let mut cards = HashSet::new();
+        if let Some(card) = players
+            .iter()
+            .flat_map(|player| player.deck.iter())
+            .find(|&&card| !cards.insert(card))
+        {
+            return Err(Error::parse(format!("card {} is dealt twice", card)));
+        }
+        Ok(Self { players })
+    }
+
+    fn part_1(&mut self) -> Result<Answer> {
+        Ok(solve_part_1(&self.players)?.into())
+    }
+
+    fn part_2(&mut self) -> Result<Answer> {
+        Ok(solve_part_2(&self.players).into())
+    }
 }
 
 #[cfg(test)]
@@ -162,8 +215,18 @@ Player 2:
 4
 7
 10"#;
-        let players: Vec<String> = test_input.split("\n\n").map(|s| s.to_string()).collect();
-        assert_eq!(solve_part_1(&players), 306);
+        let players = map_blocks(test_input, Player::new).unwrap();
+        assert_eq!(solve_part_1(&players).unwrap(), 306);
         assert_eq!(solve_part_2(&players), 291);
     }
+
+    #[test]
+    fn test_endless_combat() {
+        let players = map_blocks("Player 1:\n43\n19\n\nPlayer 2:\n2\n29\n14", Player::new).unwrap();
+        assert_eq!(
+            solve_part_1(&players).unwrap_err().to_string(),
+            "no solution: the game repeats forever"
+        );
+        assert_eq!(solve_part_2(&players), 105);
+    }
 }
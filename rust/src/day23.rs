@@ -1,10 +1,16 @@
-fn perform_move(circle: &mut Vec<u32>, current: u32, wrap_value: u32) {
+use crate::error::{Error, Result};
+use crate::generate::Rng;
+use crate::solution::{Answer, Solution};
+
+/// Performs one move of the crab. `circle[cup]` is the cup following `cup`,
+/// the cups being labelled from 1 to `wrap_value`.
+pub fn perform_move(circle: &mut [u32], current: u32, wrap_value: u32) {
     // Pick up the 3 cups immediately after the current cup.
     // Using 3 variable instead of
     let mut pickups = [0, 0, 0];
     let mut pickup = circle[current as usize]; // The first cup after the current cup
-    for i in 0..3 {
-        pickups[i] = pickup;
+    for cup in &mut pickups {
+        *cup = pickup;
         pickup = circle[pickup as usize]; // The next cup after the picked up cup
     }
     // At this point `pickup` will be the cup after the 3 picked up cups, so we link
@@ -65,9 +71,9 @@ fn solve_part_2(initial: &str, moves: usize) -> u64 {
     }
     circle[*nums.last().unwrap() as usize] = 10;
 
-    for i in 10..1_000_000 {
+    for (i, cup) in circle.iter_mut().enumerate().take(1_000_000).skip(10) {
         // The cup is in increasing order
-        circle[i] = i as u32 + 1;
+        *cup = i as u32 + 1;
     }
 
     // The last cup will point back the the first cup to form a circle
@@ -84,11 +90,41 @@ fn solve_part_2(initial: &str, moves: usize) -> u64 {
     circle[1] as u64 * circle[circle[1] as usize] as u64
 }
 
-pub fn main() {
-    println!("Day 23");
-    let initial = "538914762";
-    println!("\tPart1: {}", solve_part_1(initial, 100));
-    println!("\tPart2: {}", solve_part_2(initial, 10_000_000));
+/// The cups 1 to 9 in a random order.
+pub fn generate(rng: &mut Rng) -> String {
+    let mut cups: Vec<char> = ('1'..='9').collect();
+    rng.shuffle(&mut cups);
+    cups.into_iter().chain(Some('\n')).collect()
+}
+
+pub struct Day23 {
+    initial: String,
+}
+
+impl Solution for Day23 {
+    fn parse(input: &str) -> Result<Self> {
+        let initial = input.trim();
+        let mut labels: Vec<char> = initial.chars().collect();
+        labels.sort_unstable();
+        if labels != ['1', '2', '3', '4', '5', '6', '7', '8', '9'] {
+            return Err(Error::parse(format!(
+                "expected the cups 1 to 9 in any order, found `{}`",
+                initial
+            ))
+            .on_line(1));
+        }
+        Ok(Self {
+            initial: initial.into(),
+        })
+    }
+
+    fn part_1(&mut self) -> Result<Answer> {
+        Ok(solve_part_1(&self.initial, 100).into())
+    }
+
+    fn part_2(&mut self) -> Result<Answer> {
+        Ok(solve_part_2(&self.initial, 10_000_000).into())
+    }
 }
 
 #[cfg(test)]
@@ -1,26 +1,41 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::HashSet;
 
-use crate::utilities::read_lines;
+use crate::automaton::{Hex, Rule, Sparse, Universe};
+use crate::error::{Error, Result};
+use crate::generate::Rng;
+use crate::solution::{Answer, Solution};
+use crate::utilities::map_lines;
 
 const DIRECTIONS: [&str; 6] = ["ne", "nw", "se", "sw", "e", "w"];
-const CHANGES: [(i16, i16); 6] = [(0, 1), (-1, 1), (1, -1), (0, -1), (1, 0), (-1, 0)];
+const CHANGES: [(i32, i32); 6] = [(0, 1), (-1, 1), (1, -1), (0, -1), (1, 0), (-1, 0)];
 
-fn solve_part_1(lines: Vec<String>) -> HashSet<(i16, i16)> {
+/// Black tiles stay black with 1 or 2 black neighbours, white tiles turn black
+/// with 2.
+pub const RULE: Rule = Rule::new(&[2], &[1, 2]);
+pub const DAYS: usize = 100;
+
+/// Follows the directions of a line from the reference tile.
+fn locate_tile(line: &str) -> Result<(i32, i32)> {
+    let mut pos: (i32, i32) = (0, 0);
+    let mut s = line;
+    while !s.is_empty() {
+        let index = DIRECTIONS
+            .iter()
+            .position(|direction| s.starts_with(*direction))
+            .ok_or_else(|| Error::parse("expected one of e, se, sw, w, nw or ne").at(line, s))?;
+        pos = pos
+            .0
+            .checked_add(CHANGES[index].0)
+            .zip(pos.1.checked_add(CHANGES[index].1))
+            .ok_or_else(|| Error::parse("the path leaves the 32-bit floor").at(line, s))?;
+        s = &s[DIRECTIONS[index].len()..];
+    }
+    Ok(pos)
+}
+
+fn solve_part_1(tiles: &[(i32, i32)]) -> HashSet<(i32, i32)> {
     let mut black_tiles = HashSet::new();
-    for line in lines {
-        // Reference tile position
-        let mut pos = (0, 0);
-        let mut s = line.as_str();
-        while !s.is_empty() {
-            for (index, direction) in DIRECTIONS.iter().enumerate() {
-                if s.starts_with(*direction) {
-                    s = &s[direction.len()..];
-                    pos.0 += CHANGES[index].0;
-                    pos.1 += CHANGES[index].1;
-                    break;
-                }
-            }
-        }
+    for &pos in tiles {
         if black_tiles.contains(&pos) {
             black_tiles.remove(&pos);
         } else {
@@ -30,46 +45,53 @@ fn solve_part_1(lines: Vec<String>) -> HashSet<(i16, i16)> {
     black_tiles
 }
 
-fn solve_part_2(mut black_tiles: HashSet<(i16, i16)>) -> usize {
-    for _ in 0..100 {
-        let mut no_flip_black_tiles = HashSet::new();
-        let mut white_tiles = HashMap::new();
-        for tile in &black_tiles {
-            let mut black_neighbors = 0;
-            for change in &CHANGES {
-                let neighbor = (tile.0 + change.0, tile.1 + change.1);
-                if black_tiles.contains(&neighbor) {
-                    black_neighbors += 1;
-                } else {
-                    // This is a white neighbor, so increase the number of
-                    // black neighbors for this white neighbor by 1, because
-                    // it's a neighbor to the current black `tile`
-                    *white_tiles.entry(neighbor).or_insert(0) += 1;
-                }
-            }
-
-            if black_neighbors == 1 || black_neighbors == 2 {
-                no_flip_black_tiles.insert(*tile);
-            }
+fn exhibit(black_tiles: HashSet<(i32, i32)>) -> Sparse<Hex> {
+    Sparse::new(Hex, black_tiles)
+}
+
+/// The floor once the tiles of the input are flipped.
+pub fn lobby_floor(input: &str) -> Result<Sparse<Hex>> {
+    let tiles = map_lines(input, locate_tile)?;
+    Ok(exhibit(solve_part_1(&tiles)))
+}
+
+fn solve_part_2(black_tiles: HashSet<(i32, i32)>) -> usize {
+    let mut floor = exhibit(black_tiles);
+    floor.run(&RULE, DAYS);
+    floor.population()
+}
+
+/// About 320 random paths of 15 to 25 steps.
+pub fn generate(rng: &mut Rng) -> String {
+    let mut text = String::new();
+    for _ in 0..rng.range(300..=340) {
+        for _ in 0..rng.range(15..=25) {
+            let direction = *rng.choose(&DIRECTIONS);
+            text.push_str(direction);
         }
-        let white_to_black: HashSet<(i16, i16)> = white_tiles
-            .into_iter()
-            .filter(|(_, black_neighbors)| *black_neighbors == 2)
-            .map(|(pos, _)| pos)
-            .collect();
-        black_tiles = no_flip_black_tiles
-            .union(&white_to_black)
-            .cloned()
-            .collect::<HashSet<(i16, i16)>>();
+        text.push('\n');
     }
-    black_tiles.len()
+    text
 }
-pub fn main() {
-    println!("Day 24");
-    let lines = read_lines("../inputs/day24.txt");
-    let black_tiles = solve_part_1(lines);
-    println!("\tPart1: {}", black_tiles.len());
-    println!("\tPart2: {}", solve_part_2(black_tiles));
+
+pub struct Day24 {
+    tiles: Vec<(i32, i32)>,
+}
+
+impl Solution for Day24 {
+    fn parse(input: &str) -> Result<Self> {
+        Ok(Self {
+            tiles: map_lines(input, locate_tile)?,
+        })
+    }
+
+    fn part_1(&mut self) -> Result<Answer> {
+        Ok(solve_part_1(&self.tiles).len().into())
+    }
+
+    fn part_2(&mut self) -> Result<Answer> {
+        Ok(solve_part_2(solve_part_1(&self.tiles)).into())
+    }
 }
 
 #[cfg(test)]
@@ -99,7 +121,7 @@ eneswnwswnwsenenwnwnwwseeswneewsenese
 neswnwewnwnwseenwseesewsenwsweewe
 wseweeenwnesenwwwswnew"#;
         assert_eq!(
-            solve_part_1(input_str.lines().map(str::to_string).collect()).len(),
+            solve_part_1(&map_lines(input_str, locate_tile).unwrap()).len(),
             10
         );
     }
@@ -126,7 +148,14 @@ nenewswnwewswnenesenwnesewesw
 eneswnwswnwsenenwnwnwwseeswneewsenese
 neswnwewnwnwseenwseesewsenwsweewe
 wseweeenwnesenwwwswnew"#;
-        let black_tiles = solve_part_1(input_str.lines().map(str::to_string).collect());
+        let black_tiles = solve_part_1(&map_lines(input_str, locate_tile).unwrap());
         assert_eq!(solve_part_2(black_tiles), 2208);
     }
+
+    #[test]
+    fn test_locate_tile() {
+        assert_eq!(locate_tile("nwwswee").unwrap(), (0, 0));
+        assert_eq!(locate_tile(&"e".repeat(40_000)).unwrap(), (40_000, 0));
+        assert_eq!(locate_tile("eex").unwrap_err().column(), Some(3));
+    }
 }
@@ -1,6 +1,12 @@
+use crate::error::{Error, Result};
+use crate::generate::Rng;
+use crate::solution::{Answer, Solution};
+use crate::utilities::parse_lines;
+
 const PRIME: u64 = 20201227;
 
-fn solve_part_1(public_card: u64, public_door: u64) -> u64 {
+/// Finds the encryption key shared by the card and the door.
+pub fn encryption_key(public_card: u64, public_door: u64) -> u64 {
     let mut value = 1;
     let mut encrypt = 1;
     while value != public_card {
@@ -11,12 +17,49 @@ fn solve_part_1(public_card: u64, public_door: u64) -> u64 {
     encrypt
 }
 
-pub fn main() {
-    println!("Day 25");
+/// Public keys of random loop sizes, up to 5 million.
+pub fn generate(rng: &mut Rng) -> String {
+    let mut key = || {
+        let mut key = 1;
+        for _ in 0..rng.range(100_000..=5_000_000) {
+            key = key * 7 % PRIME;
+        }
+        key
+    };
+    format!("{}\n{}\n", key(), key())
+}
+
+pub struct Day25 {
+    public_card: u64,
+    public_door: u64,
+}
 
-    let public_card = 8335663;
-    let public_door = 8614349;
-    println!("\tPart1: {}", solve_part_1(public_card, public_door));
+impl Solution for Day25 {
+    fn parse(input: &str) -> Result<Self> {
+        let keys: Vec<u64> = parse_lines(input)?;
+        if keys.len() != 2 {
+            return Err(Error::parse(format!(
+                "expected 2 public keys, found {}",
+                keys.len()
+            )));
+        }
+        // 7 generates every value below the prime, so the loop size of such a
+        // key always exists
+        if let Some(index) = keys.iter().position(|key| !(1..PRIME).contains(key)) {
+            return Err(
+                Error::parse(format!("public keys must be between 1 and {}", PRIME - 1))
+                    .on_line(index + 1),
+            );
+        }
+        Ok(Self {
+            public_card: keys[0],
+            public_door: keys[1],
+        })
+    }
+
+    fn part_1(&mut self) -> Result<Answer> {
+        Ok(encryption_key(self.public_card, self.public_door).into())
+    }
 }
 
 #[cfg(test)]
@@ -27,6 +70,6 @@ mod tests {
     fn test_part_1() {
         let public_card = 5764801;
         let public_door = 17807724;
-        assert_eq!(solve_part_1(public_card, public_door), 14897079);
+        assert_eq!(encryption_key(public_card, public_door), 14897079);
     }
 }
@@ -1,44 +1,230 @@
-use crate::utilities::read_lines;
+use std::fmt;
+use std::ops::RangeInclusive;
 
-fn count_trees(data: &Vec<Vec<char>>, move_: &(usize, usize)) -> i32 {
-    let mut trees = 0;
-    let mut row = 0;
+use crate::error::Result;
+use crate::generate::Rng;
+use crate::grid::{Grid, Position};
+use crate::solution::{Answer, Solution};
+
+/// A move of `right` columns and `down` rows at a time, going left or up
+/// when negative.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Slope {
+    pub right: isize,
+    pub down: isize,
+}
+
+impl Slope {
+    pub fn new(right: isize, down: isize) -> Self {
+        Slope { right, down }
+    }
+}
+
+impl fmt::Display for Slope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "right {}, down {}", self.right, self.down)
+    }
+}
+
+/// The squares visited going across the map by `slope`, the map repeating
+/// itself to the left and right. Slopes going down start from the top left
+/// corner, slopes going up from the bottom left one. A slope with `down` 0
+/// never leaves the map and visits nothing.
+pub fn path(data: &Grid<char>, slope: Slope) -> Vec<Position> {
+    let (width, height) = (data.width() as isize, data.height() as isize);
+    if slope.down == 0 || width == 0 {
+        return vec![];
+    }
+    let right = slope.right.rem_euclid(width);
+    let mut row = if slope.down > 0 { 0 } else { height - 1 };
     let mut col = 0;
+    let mut path = vec![];
+    while (0..height).contains(&row) {
+        path.push((row as usize, col as usize));
+        row = row.saturating_add(slope.down);
+        col = (col + right) % width;
+    }
+    path
+}
+
+/// Counts the trees met along the `path` of `slope`.
+pub fn count_trees(data: &Grid<char>, slope: Slope) -> usize {
+    path(data, slope)
+        .into_iter()
+        .filter(|&position| data[position] == '#')
+        .count()
+}
+
+/// The trees met by every slope of a sweep.
+pub struct Sweep {
+    pub trees: Vec<(Slope, usize)>,
+}
 
-    while row < data.len() {
-        if data[row][col] == '#' {
-            trees += 1;
+impl Sweep {
+    /// The fewest trees met, along with every slope meeting that few.
+    pub fn fewest(&self) -> Option<(usize, Vec<Slope>)> {
+        let fewest = self.trees.iter().map(|&(_, trees)| trees).min()?;
+        Some((fewest, self.slopes_meeting(fewest)))
+    }
+
+    /// The most trees met, along with every slope meeting that many.
+    pub fn most(&self) -> Option<(usize, Vec<Slope>)> {
+        let most = self.trees.iter().map(|&(_, trees)| trees).max()?;
+        Some((most, self.slopes_meeting(most)))
+    }
+
+    fn slopes_meeting(&self, count: usize) -> Vec<Slope> {
+        self.trees
+            .iter()
+            .filter(|&&(_, trees)| trees == count)
+            .map(|&(slope, _)| slope)
+            .collect()
+    }
+}
+
+/// Counts the trees met by every slope moving `rights` columns and `downs`
+/// rows at a time, skipping those with `down` 0.
+pub fn sweep(
+    data: &Grid<char>,
+    rights: RangeInclusive<isize>,
+    downs: RangeInclusive<isize>,
+) -> Sweep {
+    let mut trees = vec![];
+    for right in rights {
+        for down in downs.clone().filter(|&down| down != 0) {
+            let slope = Slope::new(right, down);
+            trees.push((slope, count_trees(data, slope)));
         }
-        row += move_.1;
-        col += move_.0;
-        col %= data[0].len();
     }
+    Sweep { trees }
+}
+
+/// The map with the squares of `path` marked as in the puzzle: `X` on a
+/// tree, `O` on an open square.
+pub fn render_path(data: &Grid<char>, path: &[Position]) -> Grid<char> {
+    let mut map = data.clone();
+    for &position in path {
+        map[position] = if data[position] == '#' { 'X' } else { 'O' };
+    }
+    map
+}
 
-    trees
+fn part_1(data: &Grid<char>) -> usize {
+    count_trees(data, Slope::new(3, 1))
 }
 
-fn part_1(data: &Vec<Vec<char>>) {
-    let move_ = (3, 1);
-    println!("\tPart 1: {}", count_trees(data, &move_));
+fn part_2(data: &Grid<char>) -> i64 {
+    let slopes = [(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)];
+    slopes
+        .iter()
+        .map(|&(right, down)| count_trees(data, Slope::new(right, down)) as i64)
+        .product()
 }
 
-fn part_2(data: &Vec<Vec<char>>) {
-    let moves = vec![(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)];
-    println!(
-        "\tPart 2: {}",
-        moves
-            .iter()
-            .map(|move_| count_trees(data, move_))
-            .fold(1, |x, y| x * y)
-    );
+/// A random map of 323 rows of 31 squares, about a quarter of them trees.
+pub fn generate(rng: &mut Rng) -> String {
+    let map = Grid::from_fn(31, 323, |_| if rng.chance(0.25) { '#' } else { '.' });
+    format!("{}\n", map)
 }
 
-pub fn main() {
-    println!("Day 3");
-    let data: Vec<Vec<char>> = read_lines("../inputs/day3.txt")
-        .into_iter()
-        .map(|line| line.chars().collect())
-        .collect();
-    part_1(&data);
-    part_2(&data);
+pub struct Day3 {
+    data: Grid<char>,
+}
+
+impl Solution for Day3 {
+    fn parse(input: &str) -> Result<Self> {
+        Ok(Self {
+            data: Grid::parse(input, &['.', '#'])?,
+        })
+    }
+
+    fn part_1(&mut self) -> Result<Answer> {
+        Ok(part_1(&self.data).into())
+    }
+
+    fn part_2(&mut self) -> Result<Answer> {
+        Ok(part_2(&self.data).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "..##.......
+#...#...#..
+.#....#..#.
+..#.#...#.#
+.#...##..#.
+..#.##.....
+.#.#.#....#
+.#........#
+#.##...#...
+#...##....#
+.#..#...#.#";
+
+    #[test]
+    fn test_count_trees() {
+        let map = Grid::parse(EXAMPLE, &['.', '#']).unwrap();
+        let trees: Vec<usize> = [(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)]
+            .iter()
+            .map(|&(right, down)| count_trees(&map, Slope::new(right, down)))
+            .collect();
+        assert_eq!(trees, vec![2, 7, 3, 4, 2]);
+        assert_eq!(part_2(&map), 336);
+        assert_eq!(count_trees(&map, Slope::new(3, 0)), 0);
+        assert_eq!(
+            path(&map, Slope::new(-1, -4)),
+            vec![(10, 0), (6, 10), (2, 9)]
+        );
+        assert_eq!(path(&map, Slope::new(14, 5)), path(&map, Slope::new(3, 5)));
+    }
+
+    #[test]
+    fn test_count_trees_mirrored() {
+        let mut rng = Rng::new(5);
+        for _ in 0..100 {
+            let (width, height) = (1 + rng.below(12), 1 + rng.below(12));
+            let map = Grid::from_fn(width, height, |_| if rng.chance(0.4) { '#' } else { '.' });
+            // Turned upside down, and mirrored around the first column
+            let upside_down =
+                Grid::from_fn(width, height, |(row, col)| map[(height - 1 - row, col)]);
+            let mirrored = Grid::from_fn(width, height, |(row, col)| {
+                map[(row, (width - col) % width)]
+            });
+            let right = rng.range(-20..=20) as isize;
+            let down = rng.range(1..=5) as isize;
+            let trees = count_trees(&map, Slope::new(right, down));
+            assert_eq!(count_trees(&upside_down, Slope::new(right, -down)), trees);
+            assert_eq!(count_trees(&mirrored, Slope::new(-right, down)), trees);
+        }
+    }
+
+    #[test]
+    fn test_sweep() {
+        let map = Grid::parse(EXAMPLE, &['.', '#']).unwrap();
+        let sweep = sweep(&map, -2..=7, -1..=2);
+        assert_eq!(sweep.trees.len(), 30);
+        assert_eq!(
+            sweep
+                .trees
+                .iter()
+                .find(|(slope, _)| *slope == Slope::new(3, 1)),
+            Some(&(Slope::new(3, 1), 7))
+        );
+        assert_eq!(sweep.most(), Some((7, vec![Slope::new(3, 1)])));
+        let (fewest, slopes) = sweep.fewest().unwrap();
+        assert!(slopes
+            .iter()
+            .all(|&slope| count_trees(&map, slope) == fewest));
+        assert!(sweep.trees.iter().all(|&(_, trees)| trees >= fewest));
+        assert_eq!(super::sweep(&map, 1..=3, 0..=0).most(), None);
+    }
+
+    #[test]
+    fn test_render_path() {
+        let map = Grid::parse("..#\n#.#\n.##", &['.', '#']).unwrap();
+        let rendered = render_path(&map, &path(&map, Slope::new(1, 1)));
+        assert_eq!(rendered.to_string(), "O.#\n#O#\n.#X");
+    }
 }
@@ -1,10 +1,15 @@
-use crate::utilities::read_block;
+use crate::error::Result;
+use crate::generate::Rng;
+use crate::solution::{Answer, Solution};
+use crate::utilities::{lines, map_blocks, split_pair};
 use std::collections::HashMap;
 
+const EYE_COLORS: [&str; 7] = ["amb", "blu", "brn", "gry", "grn", "hzl", "oth"];
+
 struct Passport {
-    byr: i32,
-    iyr: i32,
-    eyr: i32,
+    byr: Option<i32>,
+    iyr: Option<i32>,
+    eyr: Option<i32>,
     hgt: String,
     hcl: String,
     ecl: String,
@@ -12,25 +17,28 @@ struct Passport {
 }
 
 impl Passport {
-    fn new(passport_info: String) -> Result<Passport, ()> {
-        let passport_info = passport_info.replace('\n', " ");
-        let infos: Vec<&str> = passport_info.split_ascii_whitespace().collect();
+    /// Returns `None` when a required field is missing.
+    fn new(passport_info: &str) -> Result<Option<Passport>> {
         let mut map = HashMap::new();
-        for info in infos {
-            let mut pair = info.split(':');
-            let key = pair.next().unwrap();
-            let value = pair.next().unwrap();
-            map.insert(key.to_string(), value.to_string());
+        for (index, line) in lines(passport_info).enumerate() {
+            for info in line.split_ascii_whitespace() {
+                let (key, value) =
+                    split_pair(line, info, ":").map_err(|error| error.on_line(index + 1))?;
+                map.insert(key, value);
+            }
         }
-        Ok(Passport {
-            byr: map.get("byr").ok_or(())?.parse().unwrap(),
-            iyr: map.get("iyr").ok_or(())?.parse().unwrap(),
-            eyr: map.get("eyr").ok_or(())?.parse().unwrap(),
-            hgt: map.get("hgt").ok_or(())?.to_string(),
-            hcl: map.get("hcl").ok_or(())?.to_string(),
-            ecl: map.get("ecl").ok_or(())?.to_string(),
-            pid: map.get("pid").ok_or(())?.to_string(),
-        })
+        let passport = || {
+            Some(Passport {
+                byr: map.get("byr")?.parse().ok(),
+                iyr: map.get("iyr")?.parse().ok(),
+                eyr: map.get("eyr")?.parse().ok(),
+                hgt: map.get("hgt")?.to_string(),
+                hcl: map.get("hcl")?.to_string(),
+                ecl: map.get("ecl")?.to_string(),
+                pid: map.get("pid")?.to_string(),
+            })
+        };
+        Ok(passport())
     }
 
     fn validate(&self) -> bool {
@@ -44,35 +52,26 @@ impl Passport {
     }
 
     fn validate_birthdate(&self) -> bool {
-        self.byr >= 1920 && self.byr <= 2002
+        matches!(self.byr, Some(1920..=2002))
     }
 
     fn validate_issue_date(&self) -> bool {
-        self.iyr >= 2010 && self.iyr <= 2020
+        matches!(self.iyr, Some(2010..=2020))
     }
 
     fn validate_expire_date(&self) -> bool {
-        self.eyr >= 2020 && self.eyr <= 2030
+        matches!(self.eyr, Some(2020..=2030))
     }
 
     fn validate_height(&self) -> bool {
         let height = self.hgt.as_str();
-        let len = height.len();
-        if len > 3 {
-            let unit = &height[len - 2..];
-            let height: i32 = height[..len - 2].parse().unwrap();
-            if unit == "cm" {
-                if height >= 150 && height <= 193 {
-                    return true;
-                }
-            } else if unit == "in" {
-                if height >= 59 && height <= 76 {
-                    return true;
-                }
-            }
+        if let Some(height) = height.strip_suffix("cm") {
+            matches!(height.parse(), Ok(150..=193))
+        } else if let Some(height) = height.strip_suffix("in") {
+            matches!(height.parse(), Ok(59..=76))
+        } else {
+            false
         }
-
-        false
     }
 
     fn validate_hair_color(&self) -> bool {
@@ -89,14 +88,7 @@ impl Passport {
     }
 
     fn validate_eye_color(&self) -> bool {
-        // amb blu brn gry grn hzl oth
-        let eye_colors = ["amb", "blu", "brn", "gry", "grn", "hzl", "oth"];
-        for i in 0..eye_colors.len() {
-            if self.ecl == eye_colors[i] {
-                return true;
-            }
-        }
-        false
+        EYE_COLORS.contains(&self.ecl.as_str())
     }
 
     fn validate_passport_id(&self) -> bool {
@@ -104,28 +96,95 @@ impl Passport {
     }
 }
 
-fn part_1(passports: &Vec<Passport>) {
-    println!("\tPart 1: {}", passports.len());
+fn part_1(passports: &[Passport]) -> usize {
+    passports.len()
+}
+
+fn part_2(passports: &[Passport]) -> usize {
+    passports
+        .iter()
+        .filter(|passport| passport.validate())
+        .count()
+}
+
+/// A year between `low` and `high`, or up to 20 years outside when invalid.
+fn random_year(rng: &mut Rng, valid: bool, low: i64, high: i64) -> String {
+    let year = if valid {
+        rng.range(low..=high)
+    } else if rng.chance(0.5) {
+        rng.range(low - 20..=low - 1)
+    } else {
+        rng.range(high + 1..=high + 20)
+    };
+    year.to_string()
+}
+
+fn random_value(rng: &mut Rng, key: &str, valid: bool) -> String {
+    let digits = |rng: &mut Rng, count: usize, set: &[u8]| -> String {
+        (0..count).map(|_| *rng.choose(set) as char).collect()
+    };
+    match key {
+        "byr" => random_year(rng, valid, 1920, 2002),
+        "iyr" => random_year(rng, valid, 2010, 2020),
+        "eyr" => random_year(rng, valid, 2020, 2030),
+        "hgt" => match (valid, rng.chance(0.5)) {
+            (true, true) => format!("{}cm", rng.range(150..=193)),
+            (true, false) => format!("{}in", rng.range(59..=76)),
+            (false, true) => format!("{}cm", rng.range(194..=200)),
+            (false, false) => rng.range(59..=193).to_string(),
+        },
+        "hcl" if valid => format!("#{}", digits(rng, 6, b"0123456789abcdef")),
+        "hcl" => digits(rng, 6, b"0123456789abcdefz"),
+        "ecl" if valid => rng.choose(&EYE_COLORS).to_string(),
+        "ecl" => rng.choose(&["xry", "zzz", "gmt", "utc", "lzr"]).to_string(),
+        "pid" => digits(rng, if valid { 9 } else { 10 }, b"0123456789"),
+        _ => rng.range(50..=350).to_string(),
+    }
+}
+
+/// A random batch of about 280 passports, some missing fields and some with
+/// invalid values.
+pub fn generate(rng: &mut Rng) -> String {
+    let mut passports = vec![];
+    for _ in 0..rng.range(260..=300) {
+        let mut fields = vec![];
+        for &key in &["byr", "iyr", "eyr", "hgt", "hcl", "ecl", "pid", "cid"] {
+            let missing = if key == "cid" { 0.4 } else { 0.04 };
+            if !rng.chance(missing) {
+                let valid = rng.chance(0.9);
+                fields.push(format!("{}:{}", key, random_value(rng, key, valid)));
+            }
+        }
+        rng.shuffle(&mut fields);
+        let mut lines = vec![];
+        while !fields.is_empty() {
+            let count = rng.range(1..=4).min(fields.len() as i64) as usize;
+            lines.push(fields.drain(..count).collect::<Vec<_>>().join(" "));
+        }
+        passports.push(lines.join("\n"));
+    }
+    passports.join("\n\n") + "\n"
 }
 
-fn part_2(passports: &Vec<Passport>) {
-    println!(
-        "\tPart 2: {}",
-        passports
-            .iter()
-            .filter(|passport| passport.validate())
-            .count()
-    );
+pub struct Day4 {
+    passports: Vec<Passport>,
 }
 
-pub fn main() {
-    println!("Day 4");
-    let passports = read_block("../inputs/day4.txt")
-        .into_iter()
-        .map(Passport::new)
-        .filter(|passport| passport.is_ok())
-        .map(|passport| passport.unwrap())
-        .collect();
-    part_1(&passports);
-    part_2(&passports);
+impl Solution for Day4 {
+    fn parse(input: &str) -> Result<Self> {
+        Ok(Self {
+            passports: map_blocks(input, Passport::new)?
+                .into_iter()
+                .flatten()
+                .collect(),
+        })
+    }
+
+    fn part_1(&mut self) -> Result<Answer> {
+        Ok(part_1(&self.passports).into())
+    }
+
+    fn part_2(&mut self) -> Result<Answer> {
+        Ok(part_2(&self.passports).into())
+    }
 }
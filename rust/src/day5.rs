@@ -1,9 +1,12 @@
-use crate::utilities::read_lines;
+use crate::error::{Error, Result};
+use crate::generate::Rng;
+use crate::solution::{Answer, Solution};
+use crate::utilities::map_lines;
 
 fn string_to_int(s: &str) -> i32 {
     let mut ans = 0;
     for ch in s.chars() {
-        ans = ans << 1;
+        ans <<= 1;
         if ch == 'B' || ch == 'R' {
             ans += 1;
         }
@@ -11,29 +14,110 @@ fn string_to_int(s: &str) -> i32 {
     ans
 }
 
-fn convert_id(s: String) -> i32 {
-    let s_ = s.as_str();
-    string_to_int(&s_[..7]) * 8 + string_to_int(&s_[7..])
+/// Decodes a boarding pass such as `FBFBBFFRLR` into its seat id.
+pub fn convert_id(s: &str) -> Result<i32> {
+    for (index, ch) in s.char_indices() {
+        let expected = if index < 7 { ['F', 'B'] } else { ['L', 'R'] };
+        if index >= 10 || !expected.contains(&ch) {
+            return Err(Error::parse(format!("unexpected character `{}`", ch)).at(s, &s[index..]));
+        }
+    }
+    if s.len() != 10 {
+        return Err(Error::parse(format!(
+            "expected a boarding pass of 10 characters, found `{}`",
+            s
+        )));
+    }
+    Ok(string_to_int(&s[..7]) * 8 + string_to_int(&s[7..]))
 }
 
-fn part_1(ids: &Vec<i32>) {
-    println!("\tPart 1: {}", ids[ids.len() - 1]);
+/// The boarding pass of a seat id in `0..1024`, inverse of `convert_id`.
+fn encode_id(id: i32) -> String {
+    let row = (0..7)
+        .rev()
+        .map(|bit| if id >> (bit + 3) & 1 == 1 { 'B' } else { 'F' });
+    let col = (0..3)
+        .rev()
+        .map(|bit| if id >> bit & 1 == 1 { 'R' } else { 'L' });
+    row.chain(col).collect()
 }
 
-fn part_2(ids: &Vec<i32>) {
-    println!(
-        "\tPart 2: {}",
-        ids.windows(2).find(|pair| pair[0] + 1 != pair[1]).unwrap()[0] + 1
-    );
+fn part_1(ids: &[i32]) -> i32 {
+    ids[ids.len() - 1]
 }
 
-pub fn main() {
-    println!("Day 5");
-    let mut ids: Vec<i32> = read_lines("../inputs/day5.txt")
-        .into_iter()
-        .map(convert_id)
+fn part_2(ids: &[i32]) -> Option<i32> {
+    ids.windows(2)
+        .find(|pair| pair[0] + 1 != pair[1])
+        .map(|pair| pair[0] + 1)
+}
+
+/// Random boarding passes of a run of seats, save the empty one in its
+/// middle.
+pub fn generate(rng: &mut Rng) -> String {
+    let first = rng.range(8..=100);
+    let last = rng.range(800..=1000);
+    let empty = rng.range(first + 1..=last - 1);
+    let mut passes: Vec<String> = (first..=last)
+        .filter(|&id| id != empty)
+        .map(|id| encode_id(id as i32))
         .collect();
-    ids.sort();
-    part_1(&ids);
-    part_2(&ids);
+    rng.shuffle(&mut passes);
+    passes.iter().map(|pass| format!("{}\n", pass)).collect()
+}
+
+pub struct Day5 {
+    ids: Vec<i32>,
+}
+
+impl Solution for Day5 {
+    fn parse(input: &str) -> Result<Self> {
+        let mut ids: Vec<i32> = map_lines(input, convert_id)?;
+        if ids.is_empty() {
+            return Err(Error::parse("expected at least one boarding pass"));
+        }
+        ids.sort();
+        Ok(Self { ids })
+    }
+
+    fn part_1(&mut self) -> Result<Answer> {
+        Ok(part_1(&self.ids).into())
+    }
+
+    fn part_2(&mut self) -> Result<Answer> {
+        part_2(&self.ids)
+            .map(Answer::from)
+            .ok_or_else(|| Error::unsolvable("there is no empty seat between the boarding passes"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{convert_id, encode_id};
+    use crate::generate::Rng;
+
+    #[test]
+    fn test_convert_id() {
+        assert_eq!(convert_id("FBFBBFFRLR").unwrap(), 357);
+        assert_eq!(convert_id("BBFFBBFRLL").unwrap(), 820);
+        assert!(convert_id("FBFBBFFRL").is_err());
+        assert!(convert_id("FBFBBFFRLB").is_err());
+    }
+
+    #[test]
+    fn test_convert_id_round_trip() {
+        for id in 0..1024 {
+            assert_eq!(convert_id(&encode_id(id)).unwrap(), id);
+        }
+        let mut rng = Rng::new(5);
+        for _ in 0..1000 {
+            let pass: String = (0..10)
+                .map(|index| {
+                    let letters = if index < 7 { ['F', 'B'] } else { ['L', 'R'] };
+                    *rng.choose(&letters)
+                })
+                .collect();
+            assert_eq!(encode_id(convert_id(&pass).unwrap()), pass);
+        }
+    }
 }
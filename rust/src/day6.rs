@@ -1,4 +1,7 @@
-use crate::utilities::read_block;
+use crate::error::{Error, Result};
+use crate::generate::Rng;
+use crate::solution::{Answer, Solution};
+use crate::utilities::{lines, map_blocks};
 
 struct Group {
     num_of_people: usize,
@@ -6,19 +9,24 @@ struct Group {
 }
 
 impl Group {
-    fn new(group: String) -> Group {
-        let group_answers: Vec<&str> = group.split('\n').collect();
+    fn new(group: &str) -> Result<Group> {
+        let group_answers: Vec<&str> = lines(group).collect();
         let num_of_people = group_answers.len();
         let mut answers = vec![0; 26];
-        for person_answer in group_answers {
-            for answer in person_answer.chars() {
+        for (index, person_answer) in group_answers.into_iter().enumerate() {
+            for (position, answer) in person_answer.char_indices() {
+                if !answer.is_ascii_lowercase() {
+                    return Err(Error::parse(format!("unexpected answer `{}`", answer))
+                        .at(person_answer, &person_answer[position..])
+                        .on_line(index + 1));
+                }
                 answers[answer as usize - 97] += 1;
             }
         }
-        Group {
+        Ok(Group {
             num_of_people,
             answers,
-        }
+        })
     }
 
     fn get_total_yes_answers(&self) -> usize {
@@ -33,32 +41,65 @@ impl Group {
     }
 }
 
-fn part_1(groups: &Vec<Group>) {
-    println!(
-        "\tPart 1: {}",
-        groups
-            .iter()
-            .map(|group| group.get_total_yes_answers())
-            .sum::<usize>()
-    );
+fn part_1(groups: &[Group]) -> usize {
+    groups
+        .iter()
+        .map(|group| group.get_total_yes_answers())
+        .sum()
 }
 
-fn part_2(groups: &Vec<Group>) {
-    println!(
-        "\tPart 2: {}",
-        groups
-            .iter()
-            .map(|group| group.get_everyone_yes())
-            .sum::<usize>()
-    );
+fn part_2(groups: &[Group]) -> usize {
+    groups.iter().map(|group| group.get_everyone_yes()).sum()
 }
 
-pub fn main() {
-    println!("Day 6");
-    let groups = read_block("../inputs/day6.txt")
-        .into_iter()
-        .map(Group::new)
-        .collect();
-    part_1(&groups);
-    part_2(&groups);
+/// Random answers of about 480 groups of 1 to 5 people, who share some of
+/// their answers.
+pub fn generate(rng: &mut Rng) -> String {
+    let mut groups = vec![];
+    for _ in 0..rng.range(460..=500) {
+        let mut letters: Vec<char> = ('a'..='z').collect();
+        rng.shuffle(&mut letters);
+        let shared = rng.below(12);
+        let people: Vec<String> = (0..rng.range(1..=5))
+            .map(|_| {
+                let mut answers: Vec<char> = letters[..shared]
+                    .iter()
+                    .copied()
+                    .chain(
+                        letters[shared..]
+                            .iter()
+                            .copied()
+                            .filter(|_| rng.chance(0.2)),
+                    )
+                    .collect();
+                if answers.is_empty() {
+                    answers.push(letters[shared]);
+                }
+                rng.shuffle(&mut answers);
+                answers.into_iter().collect()
+            })
+            .collect();
+        groups.push(people.join("\n"));
+    }
+    groups.join("\n\n") + "\n"
+}
+
+pub struct Day6 {
+    groups: Vec<Group>,
+}
+
+impl Solution for Day6 {
+    fn parse(input: &str) -> Result<Self> {
+        Ok(Self {
+            groups: map_blocks(input, Group::new)?,
+        })
+    }
+
+    fn part_1(&mut self) -> Result<Answer> {
+        Ok(part_1(&self.groups).into())
+    }
+
+    fn part_2(&mut self) -> Result<Answer> {
+        Ok(part_2(&self.groups).into())
+    }
 }
@@ -1,6 +1,9 @@
 use std::collections::{HashMap, HashSet, VecDeque};
 
-use crate::utilities::read_lines;
+use crate::error::{Error, Result};
+use crate::generate::Rng;
+use crate::solution::{Answer, Solution};
+use crate::utilities::{lines, parse_number, split_pair};
 
 struct Graph {
     parent_bags: HashMap<String, Vec<String>>,
@@ -8,40 +11,74 @@ struct Graph {
 }
 
 impl Graph {
-    fn new(regulations: Vec<String>) -> Graph {
+    fn new(regulations: &str) -> Result<Graph> {
         let mut parent_bags: HashMap<String, Vec<String>> = HashMap::new();
         let mut children_bags: HashMap<String, Vec<(usize, String)>> = HashMap::new();
 
-        let mut read_regulation = |regulation: String| {
-            let mut parts = regulation.split(" contain ");
-            let outer_bag_ = parts.next().unwrap();
-            let outer_color_end = outer_bag_.rfind(' ').unwrap();
-            let outer_bag = &outer_bag_[..outer_color_end];
-            let inner_bags = parts.next().unwrap().split(", ");
-            for bag in inner_bags {
-                if bag.contains("no other") {
-                    continue;
-                }
-                let color_start = bag.find(' ').unwrap() + 1;
-                let color_end = bag.rfind(' ').unwrap();
-                let color = &bag[color_start..color_end];
+        let mut read_regulation = |regulation: &str| -> Result<()> {
+            let (outer_bag_, inner_bags) = split_pair(regulation, regulation, " contain ")?;
+            let (outer_bag, _) = split_last_word(regulation, outer_bag_)?;
+            if inner_bags.contains("no other") {
+                return Ok(());
+            }
+            for bag in inner_bags.split(", ") {
+                let (number, bag_) = split_pair(regulation, bag, " ")?;
+                let (color, _) = split_last_word(regulation, bag_)?;
 
                 let parent = parent_bags.entry(color.to_string()).or_default();
                 parent.push(outer_bag.to_string());
 
-                let num_of_bags: usize = bag[..color_start - 1].parse().unwrap();
+                let num_of_bags: usize = parse_number(regulation, number)?;
                 let children = children_bags.entry(outer_bag.to_string()).or_default();
 
                 children.push((num_of_bags, color.to_string()));
             }
+            Ok(())
         };
-        for regulation in regulations {
-            read_regulation(regulation);
+        for (index, regulation) in lines(regulations).enumerate() {
+            read_regulation(regulation).map_err(|error| error.on_line(index + 1))?;
         }
-        Graph {
+        let graph = Graph {
             parent_bags,
             children_bags,
+        };
+        match graph.find_cycle() {
+            Some(color) => Err(Error::parse(format!(
+                "`{}` bags end up containing themselves",
+                color
+            ))),
+            None => Ok(graph),
+        }
+    }
+
+    /// Returns a bag that (indirectly) contains itself, as counting the bags
+    /// inside it would never end.
+    fn find_cycle(&self) -> Option<&str> {
+        let mut finished: HashSet<&str> = HashSet::new();
+        for start in self.children_bags.keys() {
+            let mut path: HashSet<&str> = HashSet::new();
+            let mut stack = vec![(start.as_str(), false)];
+            while let Some((color, leaving)) = stack.pop() {
+                if leaving {
+                    path.remove(color);
+                    finished.insert(color);
+                    continue;
+                }
+                if finished.contains(color) {
+                    continue;
+                }
+                if !path.insert(color) {
+                    return Some(color);
+                }
+                stack.push((color, true));
+                if let Some(children) = self.children_bags.get(color) {
+                    for (_, child) in children {
+                        stack.push((child.as_str(), false));
+                    }
+                }
+            }
         }
+        None
     }
 
     fn count_outermost_bag(&self, color: &str) -> usize {
@@ -84,17 +121,133 @@ impl Graph {
     }
 }
 
-fn part_1(graph: &Graph) {
-    println!("\tPart 1: {}", graph.count_outermost_bag("shiny gold"));
+fn split_last_word<'a>(line: &str, token: &'a str) -> Result<(&'a str, &'a str)> {
+    token.rsplit_once(' ').ok_or_else(|| {
+        Error::parse(format!("expected `<color> bags`, found `{}`", token)).at(line, token)
+    })
 }
 
-fn part_2(graph: &Graph) {
-    println!("\tPart 2: {}", graph.count_bag_inside("shiny gold"));
+const ADJECTIVES: [&str; 18] = [
+    "bright", "clear", "dark", "dim", "dotted", "drab", "dull", "faded", "light", "mirrored",
+    "muted", "pale", "plaid", "posh", "shiny", "striped", "vibrant", "wavy",
+];
+const COLORS: [&str; 33] = [
+    "aqua",
+    "beige",
+    "black",
+    "blue",
+    "bronze",
+    "brown",
+    "chartreuse",
+    "coral",
+    "crimson",
+    "cyan",
+    "fuchsia",
+    "gold",
+    "gray",
+    "green",
+    "indigo",
+    "lavender",
+    "lime",
+    "magenta",
+    "maroon",
+    "olive",
+    "orange",
+    "plum",
+    "purple",
+    "red",
+    "salmon",
+    "silver",
+    "tan",
+    "teal",
+    "tomato",
+    "turquoise",
+    "violet",
+    "white",
+    "yellow",
+];
+
+/// A random set of rules for every bag, where the shiny gold bags are both
+/// inside and around other bags. The bags are put at 9 levels, and only hold
+/// bags of deeper levels so that none end up inside themselves.
+pub fn generate(rng: &mut Rng) -> String {
+    const LEVELS: usize = 9;
+    const GOLD_LEVEL: usize = 4;
+    let mut bags: Vec<String> = ADJECTIVES
+        .iter()
+        .flat_map(|adjective| {
+            COLORS
+                .iter()
+                .map(move |color| format!("{} {}", adjective, color))
+        })
+        .collect();
+    rng.shuffle(&mut bags);
+    let gold = bags.iter().position(|bag| bag == "shiny gold").unwrap();
+    let mut levels: Vec<Vec<usize>> = vec![vec![]; LEVELS];
+    for bag in 0..bags.len() {
+        let level = if bag == gold {
+            GOLD_LEVEL
+        } else {
+            rng.below(LEVELS)
+        };
+        levels[level].push(bag);
+    }
+
+    let mut contents: Vec<Vec<(i64, usize)>> = vec![vec![]; bags.len()];
+    for (level, level_bags) in levels.iter().enumerate() {
+        let deeper = levels[level + 1..].concat();
+        for &bag in level_bags {
+            if deeper.is_empty() || (bag != gold && rng.chance(0.15)) {
+                continue;
+            }
+            for _ in 0..rng.range(1..=4) {
+                let inner = *rng.choose(&deeper);
+                if contents[bag].iter().all(|&(_, other)| other != inner) {
+                    contents[bag].push((rng.range(1..=5), inner));
+                }
+            }
+        }
+    }
+    if contents.iter().flatten().all(|&(_, inner)| inner != gold) {
+        let outer = *rng.choose(&levels[..GOLD_LEVEL].concat());
+        contents[outer].push((rng.range(1..=5), gold));
+    }
+
+    let mut text = String::new();
+    for (bag, inner) in bags.iter().zip(&contents) {
+        let inner = if inner.is_empty() {
+            "no other bags".to_string()
+        } else {
+            inner
+                .iter()
+                .map(|&(count, inner)| {
+                    let plural = if count == 1 { "" } else { "s" };
+                    format!("{} {} bag{}", count, bags[inner], plural)
+                })
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        text.push_str(&format!("{} bags contain {}.\n", bag, inner));
+    }
+    text
+}
+
+pub struct Day7 {
+    graph: Graph,
 }
 
-pub fn main() {
-    println!("Day 7");
-    let graph = Graph::new(read_lines("../inputs/day7.txt"));
-    part_1(&graph);
-    part_2(&graph);
+impl Solution for Day7 {
+    fn parse(input: &str) -> Result<Self> {
+        Ok(Self {
+            graph: Graph::new(input)?,
+        })
+    }
+
+    fn part_1(&mut self) -> Result<Answer> {
+        Ok(self.graph.count_outermost_bag("shiny gold").into())
+    }
+
+    fn part_2(&mut self) -> Result<Answer> {
+        Ok(self.graph.count_bag_inside("shiny gold").into())
+    }
 }
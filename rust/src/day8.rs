@@ -1,67 +1,241 @@
-use crate::utilities::read_lines;
-use std::mem::replace;
+use crate::error::{Error, Result};
+use crate::generate::Rng;
+use crate::solution::{Answer, Solution};
+use crate::utilities::{map_lines, parse_number, split_pair};
+use std::convert::TryFrom;
 
-fn execute_instructions(instructions: &Vec<String>) -> (i32, bool) {
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Op {
+    Nop,
+    Acc,
+    Jmp,
+}
+
+impl Op {
+    fn name(self) -> &'static str {
+        match self {
+            Op::Nop => "nop",
+            Op::Acc => "acc",
+            Op::Jmp => "jmp",
+        }
+    }
+}
+
+type Instruction = (Op, i64);
+
+fn parse_instruction(line: &str) -> Result<Instruction> {
+    let (operation, argument) = split_pair(line, line, " ")?;
+    let op = match operation {
+        "nop" => Op::Nop,
+        "acc" => Op::Acc,
+        "jmp" => Op::Jmp,
+        _ => {
+            return Err(
+                Error::parse(format!("unknown operation `{}`", operation)).at(line, operation)
+            )
+        }
+    };
+    if !argument.starts_with(['+', '-']) {
+        return Err(Error::parse("the argument must start with `+` or `-`").at(line, argument));
+    }
+    Ok((op, parse_number(line, argument)?))
+}
+
+fn format_instruction((op, argument): Instruction) -> String {
+    format!("{} {:+}", op.name(), argument)
+}
+
+/// Runs the program until it ends or an instruction comes round again,
+/// returning the accumulator and whether the program ended.
+fn execute_instructions(instructions: &[Instruction]) -> Result<(i64, bool)> {
     let mut executed = vec![false; instructions.len()];
     let mut current = 0;
-    let mut accumulator = 0;
+    let mut accumulator: i64 = 0;
     while current < instructions.len() {
         if executed[current] {
-            return (accumulator, false);
+            return Ok((accumulator, false));
         }
         executed[current] = true;
 
-        if instructions[current].contains("nop") {
-            current += 1;
-        } else if instructions[current].contains("jmp") {
-            let value = instructions[current].as_str()[5..]
-                .parse::<usize>()
-                .unwrap();
-            if instructions[current].contains('+') {
-                current += value;
-            } else {
-                current -= value;
+        let (op, argument) = instructions[current];
+        match op {
+            Op::Nop => current += 1,
+            Op::Jmp => {
+                let target = current as i128 + i128::from(argument);
+                if target < 0 {
+                    // Jumping before the first instruction never terminates normally
+                    return Ok((accumulator, false));
+                }
+                // Jumping past the last instruction ends the program
+                current = usize::try_from(target).unwrap_or(usize::MAX);
+            }
+            Op::Acc => {
+                accumulator = accumulator
+                    .checked_add(argument)
+                    .ok_or_else(|| Error::unsolvable("the accumulator overflows 64 bits"))?;
+                current += 1;
+            }
+        }
+    }
+    Ok((accumulator, true))
+}
+
+/// Switches the `jmp` or `nop` at `index`, returning whether there is one.
+fn switch(instructions: &mut [Instruction], index: usize) -> bool {
+    let op = &mut instructions[index].0;
+    *op = match *op {
+        Op::Jmp => Op::Nop,
+        Op::Nop => Op::Jmp,
+        Op::Acc => return false,
+    };
+    true
+}
+
+fn change_instruction(instructions: &mut [Instruction]) -> Result<Option<i64>> {
+    for i in 0..instructions.len() {
+        if switch(instructions, i) {
+            let result = execute_instructions(instructions);
+            switch(instructions, i);
+            let (accumulator, terminated) = result?;
+            if terminated {
+                return Ok(Some(accumulator));
             }
+        }
+    }
+    Ok(None)
+}
+
+/// A random program that ends by running its instructions in order, with
+/// forward jumps, where a single `nop` became a backward `jmp`, or a single
+/// `jmp` became a `nop` falling into a backward `jmp`. The other instructions
+/// jump back before the change, so that changing them keeps the loop.
+fn random_program(rng: &mut Rng) -> Vec<Instruction> {
+    let len = rng.range(550..=650) as usize;
+    let mut path = vec![0];
+    loop {
+        let step = if rng.chance(0.7) {
+            1
         } else {
-            accumulator += instructions[current].as_str()[4..].parse::<i32>().unwrap();
-            current += 1;
+            rng.range(2..=20) as usize
+        };
+        let next = path[path.len() - 1] + step;
+        if next >= len {
+            break;
         }
+        path.push(next);
     }
-    (accumulator, true)
+
+    let mut program: Vec<Option<(&str, i64)>> = vec![None; len];
+    for (index, &position) in path.iter().enumerate() {
+        let step = path.get(index + 1).unwrap_or(&len) - position;
+        program[position] = Some(if step > 1 {
+            ("jmp", step as i64)
+        } else if rng.chance(0.6) {
+            ("acc", rng.range(-50..=50))
+        } else {
+            ("nop", 0)
+        });
+    }
+    let changeable: Vec<usize> = (1..path.len())
+        .filter(|&index| !matches!(program[path[index]], Some(("acc", _))))
+        .collect();
+    let changed = *rng.choose(&changeable);
+    let corrupted = path[changed];
+    // Somewhere run before the change, which then loops
+    let before = |rng: &mut Rng, position: usize| path[rng.below(changed)] as i64 - position as i64;
+    match program[corrupted] {
+        Some(("nop", _)) => program[corrupted] = Some(("jmp", before(rng, corrupted))),
+        Some((_, step)) => {
+            program[corrupted] = Some(("nop", step));
+            program[corrupted + 1] = Some(("jmp", before(rng, corrupted + 1)));
+        }
+        None => unreachable!("the changed instruction is on the path"),
+    }
+
+    program
+        .into_iter()
+        .enumerate()
+        .map(|(position, instruction)| match instruction {
+            Some(("nop", 0)) if position < corrupted => {
+                (Op::Nop, rng.range(0..=corrupted as i64) - position as i64)
+            }
+            Some(("nop", 0)) => (Op::Nop, rng.range(-50..=50)),
+            Some(("nop", argument)) => (Op::Nop, argument),
+            Some(("acc", argument)) => (Op::Acc, argument),
+            Some((_, argument)) => (Op::Jmp, argument),
+            None if rng.chance(0.5) => (Op::Jmp, before(rng, position)),
+            None => (Op::Acc, rng.range(-50..=50)),
+        })
+        .collect()
 }
 
-fn change_instruction(instructions: &mut Vec<String>) -> i32 {
-    let n = instructions.len();
-    let instr_changes = [("jmp", "nop"), ("nop", "jmp")];
-    for i in 0..n {
-        for instr_change in &instr_changes {
-            if instructions[i].contains(instr_change.0) {
-                let mut new_instruction = instr_change.1.to_string();
-                new_instruction.push_str(&instructions[i].as_str()[3..]);
-                let old_instruction = replace(&mut instructions[i], new_instruction);
-                let (accumulator, terminated) = execute_instructions(instructions);
-                let _ = replace(&mut instructions[i], old_instruction);
-                if terminated {
-                    return accumulator;
-                }
+/// A random boot code of about 600 instructions that loops forever, unless
+/// exactly one `jmp` or `nop` is changed.
+pub fn generate(rng: &mut Rng) -> String {
+    loop {
+        let mut instructions = random_program(rng);
+        let terminates = |instructions: &[Instruction]| {
+            matches!(execute_instructions(instructions), Ok((_, true)))
+        };
+        if terminates(&instructions) {
+            continue;
+        }
+        let mut fixes = 0;
+        for index in 0..instructions.len() {
+            if switch(&mut instructions, index) {
+                fixes += terminates(&instructions) as usize;
+                switch(&mut instructions, index);
             }
         }
+        if fixes == 1 {
+            return instructions
+                .iter()
+                .map(|&instruction| format!("{}\n", format_instruction(instruction)))
+                .collect();
+        }
     }
-    -1
 }
 
-fn part_1(instructions: &Vec<String>) {
-    let (accumulator, _) = execute_instructions(instructions);
-    println!("\tPart 1: {}", accumulator);
+pub struct Day8 {
+    instructions: Vec<Instruction>,
 }
 
-fn part_2(instructions: &mut Vec<String>) {
-    println!("\tPart 2: {}", change_instruction(instructions));
+impl Solution for Day8 {
+    fn parse(input: &str) -> Result<Self> {
+        Ok(Self {
+            instructions: map_lines(input, parse_instruction)?,
+        })
+    }
+
+    fn part_1(&mut self) -> Result<Answer> {
+        let (accumulator, _) = execute_instructions(&self.instructions)?;
+        Ok(accumulator.into())
+    }
+
+    fn part_2(&mut self) -> Result<Answer> {
+        change_instruction(&mut self.instructions)?
+            .map(Answer::from)
+            .ok_or_else(|| Error::unsolvable("no single change makes the program terminate"))
+    }
 }
 
-pub fn main() {
-    println!("Day 8");
-    let mut instructions = read_lines("../inputs/day8.txt");
-    part_1(&instructions);
-    part_2(&mut instructions);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_malformed_programs() {
+        let mut day = Day8::parse("acc +5 \nnop +0").unwrap();
+        assert_eq!(day.part_1().unwrap(), Answer::Integer(5));
+        let mut day = Day8::parse("nop +0 \njmp -1").unwrap();
+        assert_eq!(day.part_1().unwrap(), Answer::Integer(0));
+        assert_eq!(day.part_2().unwrap(), Answer::Integer(0));
+        let mut day = Day8::parse("acc +9223372036854775807\nacc +1").unwrap();
+        assert_eq!(
+            day.part_1().unwrap_err().to_string(),
+            "no solution: the accumulator overflows 64 bits"
+        );
+        let mut day = Day8::parse("jmp +9223372036854775807\njmp -9223372036854775808").unwrap();
+        assert_eq!(day.part_1().unwrap(), Answer::Integer(0));
+    }
 }
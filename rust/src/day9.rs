@@ -1,6 +1,11 @@
-use crate::utilities::read_int_array;
+use crate::error::{Error, Result};
+use crate::generate::Rng;
+use crate::solution::{Answer, Solution};
+use crate::utilities::parse_lines;
 use std::collections::{HashSet, VecDeque};
 
+const PREAMBLE_SIZE: usize = 25;
+
 fn two_sum(preamble: &VecDeque<i64>, target: i64) -> bool {
     let mut set = HashSet::new();
     for &number in preamble {
@@ -13,23 +18,23 @@ fn two_sum(preamble: &VecDeque<i64>, target: i64) -> bool {
     false
 }
 
-fn find_invalid(numbers: &Vec<i64>) -> i64 {
+fn find_invalid(numbers: &[i64]) -> Option<i64> {
     let mut preamble = VecDeque::new();
-    for &number in &numbers[..25] {
+    for &number in &numbers[..PREAMBLE_SIZE] {
         preamble.push_back(number);
     }
 
-    for &number in &numbers[25..] {
+    for &number in &numbers[PREAMBLE_SIZE..] {
         if !two_sum(&preamble, number) {
-            return number;
+            return Some(number);
         }
         preamble.push_back(number);
         let _ = preamble.pop_front();
     }
-    unreachable!("The correct input will not reach this point")
+    None
 }
 
-fn find_weakness(numbers: &Vec<i64>, target: i64) -> i64 {
+fn find_weakness(numbers: &[i64], target: i64) -> Option<i64> {
     let mut contiguous_sums = vec![];
     contiguous_sums.push(numbers[0] + numbers[1]);
 
@@ -42,28 +47,77 @@ fn find_weakness(numbers: &Vec<i64>, target: i64) -> i64 {
 
         for (start, &sum) in contiguous_sums.iter().enumerate() {
             if sum == target {
-                return numbers[start..last + 1].iter().min().unwrap()
-                    + numbers[start..last + 1].iter().max().unwrap();
+                let range = &numbers[start..last + 1];
+                return Some(range.iter().min()? + range.iter().max()?);
             }
         }
     }
-    -1
+    None
+}
+
+/// A random list of 1000 numbers, each the sum of two of the 25 before it,
+/// save one which is the sum of a contiguous range of earlier numbers.
+pub fn generate(rng: &mut Rng) -> String {
+    let invalid_index = rng.range(500..=700) as usize;
+    let mut numbers: Vec<i64> = (0..PREAMBLE_SIZE).map(|_| rng.range(1..=50)).collect();
+    while numbers.len() < 1000 {
+        let window = &numbers[numbers.len() - PREAMBLE_SIZE..];
+        if numbers.len() == invalid_index {
+            let preamble: VecDeque<i64> = window.iter().copied().collect();
+            let start = rng.below(invalid_index - 1);
+            let end = rng.range(start as i64 + 2..=(start + 17).min(invalid_index) as i64) as usize;
+            let sum = numbers[start..end].iter().sum();
+            if !two_sum(&preamble, sum) {
+                numbers.push(sum);
+            }
+            continue;
+        }
+        // Picking the smallest of a few numbers keeps the sums from overflowing
+        let mut pick = || (0..3).map(|_| *rng.choose(window)).min().unwrap();
+        let (first, second) = (pick(), pick());
+        if first != second {
+            numbers.push(first + second);
+        }
+    }
+    numbers
+        .iter()
+        .map(|number| format!("{}\n", number))
+        .collect()
 }
 
-fn part_1(numbers: &Vec<i64>) {
-    println!("\tPart 1: {}", find_invalid(numbers));
+pub struct Day9 {
+    numbers: Vec<i64>,
 }
 
-fn part_2(numbers: &Vec<i64>) {
-    println!(
-        "\tPart 2: {}",
-        find_weakness(numbers, find_invalid(numbers))
-    );
+impl Day9 {
+    fn find_invalid(&self) -> Result<i64> {
+        find_invalid(&self.numbers).ok_or_else(|| {
+            Error::unsolvable("every number is the sum of two of the previous numbers")
+        })
+    }
 }
 
-pub fn main() {
-    println!("Day 9");
-    let numbers = read_int_array("../inputs/day9.txt");
-    part_1(&numbers);
-    part_2(&numbers);
+impl Solution for Day9 {
+    fn parse(input: &str) -> Result<Self> {
+        let numbers = parse_lines(input)?;
+        if numbers.len() <= PREAMBLE_SIZE {
+            return Err(Error::parse(format!(
+                "expected more than {} numbers, found {}",
+                PREAMBLE_SIZE,
+                numbers.len()
+            )));
+        }
+        Ok(Self { numbers })
+    }
+
+    fn part_1(&mut self) -> Result<Answer> {
+        Ok(self.find_invalid()?.into())
+    }
+
+    fn part_2(&mut self) -> Result<Answer> {
+        let invalid = self.find_invalid()?;
+        find_weakness(&self.numbers, invalid)
+            .map(Answer::from)
+            .ok_or_else(|| Error::unsolvable(format!("no contiguous range sums to {}", invalid)))
+    }
 }
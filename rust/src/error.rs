@@ -0,0 +1,148 @@
+use std::{fmt, io};
+
+#[derive(Debug)]
+pub enum ErrorKind {
+    Io(io::Error),
+    /// The input does not follow the format of the puzzle
+    Parse(String),
+    /// The input is well-formed, but the puzzle has no answer for it
+    Unsolvable(String),
+}
+
+#[derive(Debug)]
+pub struct Error {
+    kind: ErrorKind,
+    line: Option<usize>,
+    column: Option<usize>,
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    fn new(kind: ErrorKind) -> Self {
+        Self {
+            kind,
+            line: None,
+            column: None,
+        }
+    }
+
+    pub fn parse<S: Into<String>>(message: S) -> Self {
+        Self::new(ErrorKind::Parse(message.into()))
+    }
+
+    pub fn unsolvable<S: Into<String>>(message: S) -> Self {
+        Self::new(ErrorKind::Unsolvable(message.into()))
+    }
+
+    pub fn kind(&self) -> &ErrorKind {
+        &self.kind
+    }
+
+    /// 1-based line of the input where the error occurred, if known.
+    pub fn line(&self) -> Option<usize> {
+        self.line
+    }
+
+    /// 1-based column of the input where the error occurred, if known.
+    pub fn column(&self) -> Option<usize> {
+        self.column
+    }
+
+    /// Sets the line of the error, unless a parser deeper down already did.
+    pub fn on_line(mut self, line: usize) -> Self {
+        self.line.get_or_insert(line);
+        self
+    }
+
+    /// Shifts the line of the error, used when a block of the input was
+    /// parsed on its own.
+    pub fn shift_lines(mut self, offset: usize) -> Self {
+        if let Some(line) = self.line.as_mut() {
+            *line += offset;
+        }
+        self
+    }
+
+    /// Sets the column to the position of `token`, which must be a slice of
+    /// `line`.
+    pub fn at(mut self, line: &str, token: &str) -> Self {
+        if self.column.is_none() {
+            self.column = column_of(line, token);
+        }
+        self
+    }
+
+    /// Moves a column found while parsing `part` on its own to the matching
+    /// column of `line`, which `part` is a slice of.
+    pub fn within(mut self, line: &str, part: &str) -> Self {
+        if let Some(offset) = column_of(line, part) {
+            self.column = Some(self.column.map_or(offset, |column| column + offset - 1));
+        }
+        self
+    }
+}
+
+fn column_of(line: &str, token: &str) -> Option<usize> {
+    let start = line.as_ptr() as usize;
+    let position = token.as_ptr() as usize;
+    if position < start {
+        return None;
+    }
+    line.get(..position - start)
+        .map(|prefix| prefix.chars().count() + 1)
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.line, self.column) {
+            (Some(line), Some(column)) => write!(f, "line {}, column {}: ", line, column)?,
+            (Some(line), None) => write!(f, "line {}: ", line)?,
+            _ => {}
+        }
+        match &self.kind {
+            ErrorKind::Io(error) => write!(f, "{}", error),
+            ErrorKind::Parse(message) => write!(f, "{}", message),
+            ErrorKind::Unsolvable(message) => write!(f, "no solution: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.kind {
+            ErrorKind::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        Self::new(ErrorKind::Io(error))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_position() {
+        let line = "1-3 a: abcde";
+        let error = Error::parse("invalid letter")
+            .at(line, &line[4..5])
+            .on_line(2);
+        assert_eq!(error.to_string(), "line 2, column 5: invalid letter");
+
+        let error = Error::parse("missing field").on_line(1).shift_lines(4);
+        assert_eq!(error.to_string(), "line 5: missing field");
+
+        let error = Error::parse("unknown token").at(line, "elsewhere");
+        assert_eq!(error.column(), None);
+
+        let part = &line[7..];
+        let error = Error::parse("invalid password").at(part, &part[2..]);
+        assert_eq!(error.within(line, part).column(), Some(10));
+    }
+}
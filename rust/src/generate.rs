@@ -0,0 +1,138 @@
+use std::ops::RangeInclusive;
+
+use crate::{
+    day1, day10, day11, day12, day13, day14, day15, day16, day17, day18, day19, day2, day20, day21,
+    day22, day23, day24, day25, day3, day4, day5, day6, day7, day8, day9,
+};
+
+/// A seeded SplitMix64 generator: not fit for cryptography, but fast and
+/// reproducible on every platform.
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0..bound`.
+    ///
+    /// # Panics
+    ///
+    /// Panics when `bound` is 0.
+    pub fn below(&mut self, bound: usize) -> usize {
+        assert!(bound > 0, "cannot draw a number below 0");
+        ((u128::from(self.next_u64()) * bound as u128) >> 64) as usize
+    }
+
+    pub fn range(&mut self, range: RangeInclusive<i64>) -> i64 {
+        let (low, high) = range.into_inner();
+        assert!(low <= high, "cannot draw a number in an empty range");
+        let span = high.abs_diff(low) as u128 + 1;
+        low.wrapping_add(((u128::from(self.next_u64()) * span) >> 64) as i64)
+    }
+
+    /// Returns `true` with the given probability.
+    pub fn chance(&mut self, probability: f64) -> bool {
+        ((self.next_u64() >> 11) as f64 / (1u64 << 53) as f64) < probability
+    }
+
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len())]
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for index in (1..items.len()).rev() {
+            items.swap(index, self.below(index + 1));
+        }
+    }
+}
+
+pub type Generator = fn(&mut Rng) -> String;
+
+/// Generators of a valid random input of every day, indexed by `day - 1`.
+pub const GENERATORS: [Generator; 25] = [
+    day1::generate,
+    day2::generate,
+    day3::generate,
+    day4::generate,
+    day5::generate,
+    day6::generate,
+    day7::generate,
+    day8::generate,
+    day9::generate,
+    day10::generate,
+    day11::generate,
+    day12::generate,
+    day13::generate,
+    day14::generate,
+    day15::generate,
+    day16::generate,
+    day17::generate,
+    day18::generate,
+    day19::generate,
+    day20::generate,
+    day21::generate,
+    day22::generate,
+    day23::generate,
+    day24::generate,
+    day25::generate,
+];
+
+/// The input of `day` generated from `seed`, always the same for a seed.
+pub fn generate(day: usize, seed: u64) -> String {
+    GENERATORS[day - 1](&mut Rng::new(seed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::SOLVERS;
+
+    #[test]
+    fn test_rng() {
+        let mut rng = Rng::new(7);
+        for _ in 0..1000 {
+            assert!(rng.below(3) < 3);
+            assert!((-2..=2).contains(&rng.range(-2..=2)));
+        }
+        assert_eq!(rng.range(5..=5), 5);
+        assert_eq!(rng.range(i64::MIN..=i64::MIN), i64::MIN);
+        let mut items: Vec<usize> = (0..10).collect();
+        rng.shuffle(&mut items);
+        items.sort_unstable();
+        assert_eq!(items, (0..10).collect::<Vec<_>>());
+        assert_eq!(Rng::new(1).next_u64(), Rng::new(1).next_u64());
+    }
+
+    #[test]
+    fn test_generators() {
+        for day in 1..=25 {
+            for seed in 0..2 {
+                let input = generate(day, seed);
+                assert_eq!(input, generate(day, seed));
+                let mut solution = SOLVERS[day - 1](&input)
+                    .unwrap_or_else(|error| panic!("day {} seed {}: {}", day, seed, error));
+                if let Err(error) = solution.part_1() {
+                    panic!("day {} seed {}, part 1: {}", day, seed, error);
+                }
+                // The second parts of days 15 and 23 are too slow without
+                // optimizations
+                if day != 15 && day != 23 {
+                    if let Err(error) = solution.part_2() {
+                        panic!("day {} seed {}, part 2: {}", day, seed, error);
+                    }
+                }
+            }
+        }
+    }
+}
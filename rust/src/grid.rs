@@ -0,0 +1,345 @@
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+use crate::error::{Error, Result};
+use crate::utilities::map_lines;
+
+/// A `(row, column)` position in a grid.
+pub type Position = (usize, usize);
+
+/// `(row, column)` steps to the 4 orthogonal neighbours.
+pub const ORTHOGONAL: [(isize, isize); 4] = [(-1, 0), (0, -1), (0, 1), (1, 0)];
+
+/// `(row, column)` steps to the 8 neighbours, diagonals included.
+pub const ADJACENT: [(isize, isize); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+
+/// A rectangular grid stored row after row.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    /// Builds a grid by calling `cell` on every position, row after row.
+    pub fn from_fn<F>(width: usize, height: usize, mut cell: F) -> Self
+    where
+        F: FnMut(Position) -> T,
+    {
+        let cells = (0..height)
+            .flat_map(|row| (0..width).map(move |col| (row, col)))
+            .map(&mut cell)
+            .collect();
+        Grid {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    /// Returns `None` when the rows do not all have the same length.
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Option<Self> {
+        let height = rows.len();
+        let width = rows.first().map_or(0, Vec::len);
+        if rows.iter().any(|row| row.len() != width) {
+            return None;
+        }
+        Some(Grid {
+            width,
+            height,
+            cells: rows.into_iter().flatten().collect(),
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, (row, col): Position) -> Option<&T> {
+        if row < self.height && col < self.width {
+            Some(&self.cells[row * self.width + col])
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, (row, col): Position) -> Option<&mut T> {
+        if row < self.height && col < self.width {
+            Some(&mut self.cells[row * self.width + col])
+        } else {
+            None
+        }
+    }
+
+    /// Reads the grid as a tile repeating itself in every direction.
+    pub fn get_wrapping(&self, row: isize, col: isize) -> &T {
+        let row = row.rem_euclid(self.height as isize) as usize;
+        let col = col.rem_euclid(self.width as isize) as usize;
+        &self[(row, col)]
+    }
+
+    /// Moves one `step` from `position`, unless that leaves the grid.
+    pub fn offset(&self, (row, col): Position, step: (isize, isize)) -> Option<Position> {
+        let row = row.checked_add_signed(step.0)?;
+        let col = col.checked_add_signed(step.1)?;
+        if row < self.height && col < self.width {
+            Some((row, col))
+        } else {
+            None
+        }
+    }
+
+    /// The neighbours of `position` reached by `steps`, such as `ADJACENT`.
+    pub fn neighbors<'a>(
+        &'a self,
+        position: Position,
+        steps: &'a [(isize, isize)],
+    ) -> impl Iterator<Item = Position> + 'a {
+        steps
+            .iter()
+            .filter_map(move |&step| self.offset(position, step))
+    }
+
+    /// The positions met moving by `step` from `position`, excluded, until
+    /// leaving the grid.
+    pub fn ray(
+        &self,
+        position: Position,
+        step: (isize, isize),
+    ) -> impl Iterator<Item = Position> + '_ {
+        let mut position = Some(position);
+        std::iter::from_fn(move || {
+            position = self.offset(position?, step);
+            position
+        })
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = Position> {
+        let width = self.width;
+        (0..self.height).flat_map(move |row| (0..width).map(move |col| (row, col)))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.cells.iter()
+    }
+
+    pub fn row(&self, row: usize) -> &[T] {
+        &self.cells[row * self.width..(row + 1) * self.width]
+    }
+
+    pub fn column(&self, col: usize) -> impl Iterator<Item = &T> {
+        self.cells.iter().skip(col).step_by(self.width.max(1))
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        (0..self.height).map(move |row| self.row(row))
+    }
+
+    pub fn map<U, F>(&self, f: F) -> Grid<U>
+    where
+        F: FnMut(&T) -> U,
+    {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn new(width: usize, height: usize, value: T) -> Self {
+        Grid {
+            width,
+            height,
+            cells: vec![value; width * height],
+        }
+    }
+
+    /// Turns the grid a quarter counterclockwise.
+    pub fn rotate(&self) -> Self {
+        Grid::from_fn(self.height, self.width, |(row, col)| {
+            self[(col, self.width - 1 - row)].clone()
+        })
+    }
+
+    /// Turns the grid upside down.
+    pub fn flip(&self) -> Self {
+        Grid::from_fn(self.width, self.height, |(row, col)| {
+            self[(self.height - 1 - row, col)].clone()
+        })
+    }
+
+    /// The 8 ways to turn the grid, starting with the grid itself and ending
+    /// with its flipped rotations.
+    pub fn orientations(&self) -> Vec<Self> {
+        let mut orientations = vec![self.clone()];
+        for index in 1..8 {
+            let previous = &orientations[index - 1];
+            orientations.push(if index == 4 {
+                previous.rotate().flip()
+            } else {
+                previous.rotate()
+            });
+        }
+        orientations
+    }
+
+    /// Copies the `height` by `width` part whose top left corner is `corner`.
+    pub fn sub_grid(&self, corner: Position, width: usize, height: usize) -> Self {
+        Grid::from_fn(width, height, |(row, col)| {
+            self[(corner.0 + row, corner.1 + col)].clone()
+        })
+    }
+
+    /// Surrounds the grid with `margin` rows and columns of `value`.
+    pub fn pad(&self, margin: usize, value: T) -> Self {
+        Grid::from_fn(
+            self.width + 2 * margin,
+            self.height + 2 * margin,
+            |(row, col)| {
+                row.checked_sub(margin)
+                    .zip(col.checked_sub(margin))
+                    .and_then(|position| self.get(position))
+                    .unwrap_or(&value)
+                    .clone()
+            },
+        )
+    }
+}
+
+impl Grid<char> {
+    /// Parses a grid of characters, checking that every row has the same
+    /// length and only contains `allowed` characters.
+    pub fn parse(input: &str, allowed: &[char]) -> Result<Self> {
+        let rows = map_lines(input, |line| {
+            if let Some((index, c)) = line.char_indices().find(|(_, c)| !allowed.contains(c)) {
+                let error = Error::parse(format!("unexpected character `{}`", c));
+                return Err(error.at(line, &line[index..]));
+            }
+            Ok(line.chars().collect::<Vec<char>>())
+        })?;
+        let width = match rows.first() {
+            Some(row) if !row.is_empty() => row.len(),
+            _ => return Err(Error::parse("the grid is empty").on_line(1)),
+        };
+        match rows.iter().position(|row| row.len() != width) {
+            Some(index) => Err(Error::parse(format!(
+                "expected a row of {} characters, found {}",
+                width,
+                rows[index].len()
+            ))
+            .on_line(index + 1)),
+            None => Ok(Grid::from_rows(rows).expect("rows of the same length")),
+        }
+    }
+}
+
+impl<T> Index<Position> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, position: Position) -> &T {
+        self.get(position).unwrap_or_else(|| {
+            panic!(
+                "position {:?} is outside a {}x{} grid",
+                position, self.width, self.height
+            )
+        })
+    }
+}
+
+impl<T> IndexMut<Position> for Grid<T> {
+    fn index_mut(&mut self, position: Position) -> &mut T {
+        let (width, height) = (self.width, self.height);
+        self.get_mut(position).unwrap_or_else(|| {
+            panic!(
+                "position {:?} is outside a {}x{} grid",
+                position, width, height
+            )
+        })
+    }
+}
+
+/// Writes one line per row.
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, row) in self.rows().enumerate() {
+            if index > 0 {
+                writeln!(f)?;
+            }
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let grid = Grid::parse("#..\r\n.##\n", &['.', '#']).unwrap();
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid[(1, 2)], '#');
+        assert_eq!(grid.get((2, 0)), None);
+        assert_eq!(grid.to_string(), "#..\n.##");
+
+        let error = Grid::parse("#.\n.", &['.', '#']).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 2: expected a row of 2 characters, found 1"
+        );
+        assert!(Grid::parse("#x", &['.', '#']).is_err());
+        assert!(Grid::parse("", &['.', '#']).is_err());
+    }
+
+    #[test]
+    fn test_neighbors() {
+        let grid = Grid::from_fn(3, 3, |(row, col)| row * 3 + col);
+        let corner: Vec<usize> = grid
+            .neighbors((0, 0), &ADJACENT)
+            .map(|position| grid[position])
+            .collect();
+        assert_eq!(corner, vec![1, 3, 4]);
+        assert_eq!(grid.neighbors((1, 1), &ORTHOGONAL).count(), 4);
+        assert_eq!(
+            grid.ray((2, 0), (-1, 1)).collect::<Vec<_>>(),
+            vec![(1, 1), (0, 2)]
+        );
+        assert_eq!(*grid.get_wrapping(-1, 4), 7);
+    }
+
+    #[test]
+    fn test_transform() {
+        let grid = Grid::from_rows(vec![vec![1, 2, 3], vec![4, 5, 6]]).unwrap();
+        assert_eq!(grid.rotate().to_string(), "36\n25\n14");
+        assert_eq!(grid.flip().to_string(), "456\n123");
+        let orientations = grid.orientations();
+        assert_eq!(orientations.len(), 8);
+        assert_eq!(orientations[4], grid.flip());
+        assert!(orientations[1..]
+            .iter()
+            .all(|orientation| *orientation != grid));
+        assert_eq!(grid.sub_grid((0, 1), 2, 2).to_string(), "23\n56");
+        assert_eq!(grid.pad(1, 0).to_string(), "00000\n01230\n04560\n00000");
+        assert_eq!(grid.column(1).copied().collect::<Vec<_>>(), vec![2, 5]);
+        assert!(Grid::from_rows(vec![vec![1], vec![]]).is_none());
+    }
+}
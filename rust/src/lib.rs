@@ -0,0 +1,54 @@
+//! Solutions of Advent of Code 2020.
+//!
+//! Every day has a module exposing its solver, `dayN::DayN`, along with the
+//! algorithms it is built on. The solvers share the [`Solution`] trait:
+//!
+//! ```
+//! use aoc2020::day1::Day1;
+//! use aoc2020::{Answer, Solution};
+//!
+//! let mut day = Day1::parse("1721\n979\n366\n299\n675\n1456").unwrap();
+//! assert_eq!(day.part_1().unwrap(), Answer::Integer(514579));
+//! ```
+
+pub mod answers;
+pub mod automaton;
+pub mod cache;
+pub mod day1;
+pub mod day10;
+pub mod day11;
+pub mod day12;
+pub mod day13;
+pub mod day14;
+pub mod day15;
+pub mod day16;
+pub mod day17;
+pub mod day18;
+pub mod day19;
+pub mod day2;
+pub mod day20;
+pub mod day21;
+pub mod day22;
+pub mod day23;
+pub mod day24;
+pub mod day25;
+pub mod day3;
+pub mod day4;
+pub mod day5;
+pub mod day6;
+pub mod day7;
+pub mod day8;
+pub mod day9;
+pub mod error;
+pub mod generate;
+pub mod grid;
+pub mod memory;
+pub mod output;
+pub mod runner;
+pub mod solution;
+pub mod utilities;
+pub mod visualize;
+pub mod workspace;
+
+pub use error::{Error, ErrorKind, Result};
+pub use solution::{Answer, Solution, Solver, SOLVERS};
@@ -1,54 +1,469 @@
-mod day1;
-mod day10;
-mod day11;
-mod day12;
-mod day13;
-mod day14;
-mod day15;
-mod day16;
-mod day17;
-mod day18;
-mod day19;
-mod day2;
-mod day20;
-mod day21;
-mod day22;
-mod day23;
-mod day24;
-mod day25;
-mod day3;
-mod day4;
-mod day5;
-mod day6;
-mod day7;
-mod day8;
-mod day9;
-mod utilities;
+use std::collections::BTreeMap;
+use std::io::{self, Read, Write};
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::{env, fs, iter, process, thread};
+
+use aoc2020::answers::{self, Answers, Verdict};
+use aoc2020::automaton::{self, Rule};
+use aoc2020::cache::Cache;
+use aoc2020::memory::{self, TrackingAllocator, Usage};
+use aoc2020::output::{self, Format};
+use aoc2020::runner::{self, BenchReport, DayReport, Stats};
+use aoc2020::Result;
+use aoc2020::{day2, generate};
+use aoc2020::{visualize, workspace};
+use cli::{Command, Input, Options};
+
+mod cli;
+
+#[global_allocator]
+static ALLOCATOR: TrackingAllocator = TrackingAllocator;
+
+/// Generations after which `life` gives up waiting for the cells to settle
+const SETTLE_LIMIT: usize = 10_000;
+/// Size of the square of pixels drawing a cell in the saved frames
+const FRAME_SCALE: usize = 4;
 
 fn main() {
-    day1::main();
-    day2::main();
-    day3::main();
-    day4::main();
-    day5::main();
-    day6::main();
-    day7::main();
-    day8::main();
-    day9::main();
-    day10::main();
-    day11::main();
-    day12::main();
-    day13::main();
-    day14::main();
-    day15::main();
-    day16::main();
-    day17::main();
-    day18::main();
-    day19::main();
-    day20::main();
-    day21::main();
-    day22::main();
-    day23::main();
-    day24::main();
-    day25::main();
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+        println!("{}", cli::USAGE);
+        return;
+    }
+
+    let options = match Options::parse(args.into_iter()) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{}\n\n{}", message, cli::USAGE);
+            process::exit(2);
+        }
+    };
+
+    if let Command::Generate { seed } = options.command {
+        let seed = seed.unwrap_or_else(|| {
+            let seed = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |time| time.as_nanos() as u64);
+            // Tell how to generate the same input again
+            eprintln!("Seed: {}", seed);
+            seed
+        });
+        print!("{}", generate::generate(options.days[0], seed));
+        return;
+    }
+
+    let cache = Cache::new(Cache::default_dir());
+    if let (Command::Matrix, Input::Dir(dir)) = (&options.command, &options.input) {
+        let cache = if options.cache { Some(&cache) } else { None };
+        match run_matrix(dir, &options, cache) {
+            Ok(true) => {}
+            Ok(false) => process::exit(1),
+            Err(error) => {
+                eprintln!("Cannot read the inputs in {}: {}", dir.display(), error);
+                process::exit(1);
+            }
+        }
+        return;
+    }
+    if let Command::ClearCache = options.command {
+        match cache.clear() {
+            Ok(count) => println!(
+                "Removed {} cached answers from {}",
+                count,
+                cache.dir().display()
+            ),
+            Err(error) => {
+                eprintln!("Cannot clear {}: {}", cache.dir().display(), error);
+                process::exit(1);
+            }
+        }
+        return;
+    }
+
+    if options.format == Format::Csv {
+        println!("{}", output::CSV_HEADER);
+    }
+    let expected = match &options.command {
+        Command::Verify { answers } => match read_answers(answers) {
+            Ok(answers) => answers,
+            Err(error) => {
+                eprintln!(
+                    "Cannot read the answers in {}: {}",
+                    answers.display(),
+                    error
+                );
+                process::exit(1);
+            }
+        },
+        _ => Answers::default(),
+    };
+    let inputs: Vec<(usize, String)> = options
+        .days
+        .iter()
+        .map(|&day| match read_input(&options.input, day) {
+            Ok(input) => (day, input),
+            Err(error) => {
+                eprintln!("Cannot read the input of day {}: {}", day, error);
+                process::exit(1);
+            }
+        })
+        .collect();
+    let mut verdicts = vec![];
+    let mut failed = false;
+    if let Command::Bench { runs } = options.command {
+        for (day, input) in &inputs {
+            match runner::bench_day(*day, options.part, input, runs) {
+                Ok(report) => print_bench(&report),
+                Err(error) => {
+                    eprintln!("Day {} failed: {}", day, error);
+                    failed = true;
+                }
+            }
+        }
+    } else if let Command::Life { rule, generations } = options.command {
+        for (day, input) in &inputs {
+            let part = options.part.unwrap_or(1);
+            if let Err(error) = run_life(*day, part, input, rule, generations) {
+                eprintln!("Day {} failed: {}", day, error);
+                failed = true;
+            }
+        }
+    } else if let Command::Report { policy } = &options.command {
+        for (day, input) in &inputs {
+            let reports = match policy {
+                Some(policy) => day2::report_with(input, policy),
+                None => day2::report(input, options.part),
+            };
+            match reports {
+                Ok(reports) if options.format == Format::Json => {
+                    for report in reports {
+                        println!("{}", report.to_json());
+                    }
+                }
+                Ok(reports) => print!("{}", day2::report_table(&reports)),
+                Err(error) => {
+                    eprintln!("Day {} failed: {}", day, error);
+                    failed = true;
+                }
+            }
+        }
+    } else if let Command::Visualize { frames, delay } = &options.command {
+        for (day, input) in &inputs {
+            let part = options.part.unwrap_or(1);
+            if let Err(error) = show_frames(*day, part, input, frames.as_deref(), *delay) {
+                eprintln!("Day {} failed: {}", day, error);
+                failed = true;
+            }
+        }
+    } else {
+        let cache = if options.cache { Some(&cache) } else { None };
+        runner::run_days(&inputs, options.part, options.jobs, cache, |report| {
+            if let Command::Verify { .. } = options.command {
+                println!("Day {}", report.day);
+                for (part, verdict) in answers::verify(&report, &expected) {
+                    print_verdict(part, &verdict);
+                    failed |= verdict.is_failure();
+                    verdicts.push(verdict);
+                }
+                return;
+            }
+            match options.format {
+                Format::Text => print_report(&report, options.time, options.mem),
+                Format::Json => {
+                    for record in output::records(&report) {
+                        println!("{}", record.to_json());
+                    }
+                }
+                Format::Csv => {
+                    for record in output::records(&report) {
+                        println!("{}", record.to_csv());
+                    }
+                }
+            }
+            failed |= !report.succeeded();
+        });
+    }
+    if let Command::Verify { .. } = options.command {
+        print_summary(&verdicts);
+    }
+    if failed {
+        process::exit(1);
+    }
+}
+
+fn read_input(input: &Input, day: usize) -> Result<String> {
+    Ok(match input {
+        Input::Dir(dir) => fs::read_to_string(dir.join(format!("day{}.txt", day)))?,
+        Input::File(file) => fs::read_to_string(file)?,
+        Input::Stdin => {
+            let mut content = String::new();
+            io::stdin().read_to_string(&mut content)?;
+            content
+        }
+    })
+}
+
+/// Prints a table of the answers of every user (columns) to every part
+/// (rows), followed by the errors met. Returns whether every answer was
+/// found and matched the expected one.
+fn run_matrix(dir: &Path, options: &Options, cache: Option<&Cache>) -> Result<bool> {
+    let users = workspace::users(dir)?;
+    if users.is_empty() {
+        eprintln!("No input found in {}", dir.display());
+        return Ok(false);
+    }
+    let mut expected = vec![];
+    let mut inputs = vec![];
+    let mut owners = vec![];
+    for (index, user) in users.iter().enumerate() {
+        expected.push(user.answers()?);
+        for &day in &options.days {
+            if let Some(input) = user.input(day)? {
+                inputs.push((day, input));
+                owners.push(index);
+            }
+        }
+    }
+
+    let parts = options.part.map_or(vec![1, 2], |part| vec![part]);
+    let mut cells: BTreeMap<(usize, u8), Vec<String>> = BTreeMap::new();
+    let mut errors = vec![];
+    let mut verdicts = vec![];
+    let mut owners = owners.into_iter();
+    runner::run_days(&inputs, options.part, options.jobs, cache, |report| {
+        let user = owners.next().unwrap();
+        let mut set = |part: u8, cell: String| {
+            cells
+                .entry((report.day, part))
+                .or_insert_with(|| vec!["-".to_string(); users.len()])[user] = cell;
+        };
+        let reports = match &report.parts {
+            Ok(reports) => reports,
+            Err(error) => {
+                for &part in &parts {
+                    set(part, "invalid input".to_string());
+                }
+                errors.push(format!(
+                    "{}, day {}: {}",
+                    users[user].name, report.day, error
+                ));
+                verdicts.push(Verdict::Fail(error.to_string()));
+                return;
+            }
+        };
+        let checked = answers::verify(&report, &expected[user]);
+        for (part, (_, verdict)) in reports.iter().zip(checked) {
+            let mut details = vec![];
+            if options.time {
+                details.push(if part.cached {
+                    "cached".to_string()
+                } else {
+                    format!("{:.2?}", part.time)
+                });
+            }
+            let answer = match (&part.answer, &verdict) {
+                (Ok(answer), Verdict::Mismatch { expected, .. }) => {
+                    details.push(format!("expected {}", expected));
+                    answer.to_string()
+                }
+                (Ok(answer), _) => answer.to_string(),
+                (Err(error), _) => {
+                    errors.push(format!(
+                        "{}, day {} part {}: {}",
+                        users[user].name, report.day, part.part, error
+                    ));
+                    "failed".to_string()
+                }
+            };
+            let cell = if details.is_empty() {
+                answer
+            } else {
+                format!("{} ({})", answer, details.join(", "))
+            };
+            set(part.part, cell);
+            verdicts.push(verdict);
+        }
+    });
+
+    let mut rows = vec![iter::once(String::new())
+        .chain(users.iter().map(|user| user.name.clone()))
+        .collect::<Vec<_>>()];
+    for ((day, part), cells) in cells {
+        rows.push(
+            iter::once(format!("Day {} part {}", day, part))
+                .chain(cells)
+                .collect(),
+        );
+    }
+    print!("{}", output::table(&rows));
+    for error in &errors {
+        eprintln!("{}", error);
+    }
+    print_summary(&verdicts);
+    Ok(!verdicts.iter().any(Verdict::is_failure))
+}
+
+fn run_life(
+    day: usize,
+    part: u8,
+    input: &str,
+    rule: Option<Rule>,
+    generations: Option<usize>,
+) -> Result<()> {
+    let mut automaton = automaton::automaton(day, part, input)?;
+    let rule = rule.unwrap_or(automaton.rule);
+    let universe = &mut automaton.universe;
+    print!("Day {}, {}: ", day, rule);
+    match generations.or(automaton.generations) {
+        Some(generations) => {
+            universe.run(&rule, generations);
+            println!(
+                "{} live cells after {} generations",
+                universe.population(),
+                generations
+            );
+        }
+        None => match universe.settle(&rule, SETTLE_LIMIT) {
+            Some(generations) => println!(
+                "{} live cells, settled after {} generations",
+                universe.population(),
+                generations
+            ),
+            None => println!(
+                "{} live cells, still changing after {} generations",
+                universe.population(),
+                SETTLE_LIMIT
+            ),
+        },
+    }
+    Ok(())
+}
+
+/// Saves the frames as `day<N>_<frame>.ppm` files in `dir`, or draws them in
+/// the terminal.
+fn show_frames(
+    day: usize,
+    part: u8,
+    input: &str,
+    dir: Option<&Path>,
+    delay: Duration,
+) -> Result<()> {
+    if let Some(dir) = dir {
+        fs::create_dir_all(dir)?;
+    }
+    let mut index = 0;
+    visualize::frames(day, part, input, |frame| {
+        match dir {
+            Some(dir) => fs::write(
+                dir.join(format!("day{}_{:04}.ppm", day, index)),
+                visualize::to_ppm(&frame, FRAME_SCALE),
+            )?,
+            None => {
+                // Draw over the previous frame
+                print!("\x1b[H\x1b[2J{}", visualize::to_ansi(&frame));
+                io::stdout().flush()?;
+                thread::sleep(delay);
+            }
+        }
+        index += 1;
+        Ok(())
+    })?;
+    if let Some(dir) = dir {
+        println!("Day {}: saved {} frames in {}", day, index, dir.display());
+    }
+    Ok(())
+}
+
+fn read_answers(path: &std::path::Path) -> Result<Answers> {
+    Answers::parse(&fs::read_to_string(path)?)
+}
+
+fn print_summary(verdicts: &[Verdict]) {
+    let count = |f: fn(&Verdict) -> bool| verdicts.iter().filter(|&verdict| f(verdict)).count();
+    println!(
+        "{} passed, {} mismatched, {} failed, {} without an expected answer",
+        count(|verdict| *verdict == Verdict::Pass),
+        count(|verdict| matches!(verdict, Verdict::Mismatch { .. })),
+        count(|verdict| matches!(verdict, Verdict::Fail(_))),
+        count(|verdict| matches!(verdict, Verdict::Unknown(_))),
+    );
+}
+
+fn print_verdict(part: Option<u8>, verdict: &Verdict) {
+    let name = match part {
+        Some(part) => format!("Part {}", part),
+        None => "Input".to_string(),
+    };
+    match verdict {
+        Verdict::Pass => println!("\t{}: pass", name),
+        Verdict::Mismatch { expected, actual } => println!(
+            "\t{}: mismatch, expected {} but found {}",
+            name, expected, actual
+        ),
+        Verdict::Fail(error) => println!("\t{}: fail, {}", name, error),
+        Verdict::Unknown(actual) => println!("\t{}: unknown, found {}", name, actual),
+    }
+}
+
+fn print_report(report: &DayReport, time: bool, mem: bool) {
+    let details = |duration: Duration, memory: Usage| {
+        let mut details = vec![];
+        if time {
+            details.push(format!("{:.2?}", duration));
+        }
+        if mem {
+            details.push(format!(
+                "peak {}, allocated {}",
+                memory::format_bytes(memory.peak),
+                memory::format_bytes(memory.allocated)
+            ));
+        }
+        details.join(", ")
+    };
+    println!("Day {}", report.day);
+    // Answers all read from the cache did not parse anything
+    let cached = matches!(&report.parts, Ok(parts) if parts.iter().all(|part| part.cached));
+    if (time || mem) && !cached {
+        println!(
+            "\tParse: {}",
+            details(report.parse_time, report.parse_memory)
+        );
+    }
+    let parts = match &report.parts {
+        Ok(parts) => parts,
+        Err(error) => {
+            eprintln!("\tInvalid input: {}", error);
+            return;
+        }
+    };
+    for part in parts {
+        match &part.answer {
+            Ok(answer) if time && part.cached => {
+                println!("\tPart {}: {} (cached)", part.part, answer)
+            }
+            Ok(answer) if time || mem => println!(
+                "\tPart {}: {} ({})",
+                part.part,
+                answer,
+                details(part.time, part.memory)
+            ),
+            Ok(answer) => println!("\tPart {}: {}", part.part, answer),
+            Err(error) => eprintln!("\tPart {} failed: {}", part.part, error),
+        }
+    }
+}
+
+fn print_bench(report: &BenchReport) {
+    let print_stats = |name: &str, stats: &Stats| {
+        println!(
+            "\t{:<8} min {:>10.2?}   median {:>10.2?}   mean {:>10.2?}",
+            name, stats.min, stats.median, stats.mean
+        );
+    };
+    println!("Day {}", report.day);
+    print_stats("Parse", &report.parse);
+    for (part, stats) in &report.parts {
+        print_stats(&format!("Part {}", part), stats);
+    }
 }
@@ -0,0 +1,118 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+static TOTAL: AtomicUsize = AtomicUsize::new(0);
+
+/// The system allocator, counting the bytes allocated through it. Install it
+/// with `#[global_allocator]`, or every `Usage` is zero.
+pub struct TrackingAllocator;
+
+fn allocated(size: usize) {
+    TOTAL.fetch_add(size, Ordering::Relaxed);
+    let current = CURRENT.fetch_add(size, Ordering::Relaxed) + size;
+    PEAK.fetch_max(current, Ordering::Relaxed);
+}
+
+unsafe impl GlobalAlloc for TrackingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            allocated(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            allocated(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        CURRENT.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            // Counted as freeing the old block for a new one
+            CURRENT.fetch_sub(layout.size(), Ordering::Relaxed);
+            allocated(new_size);
+        }
+        new_ptr
+    }
+}
+
+/// The memory used while running some code.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Usage {
+    /// Most bytes held at once, on top of those held before
+    pub peak: usize,
+    /// Bytes allocated in total, freed since or not
+    pub allocated: usize,
+}
+
+/// Runs `f`, along with the memory it used. The allocations of every thread
+/// are counted, so other threads must be idle meanwhile.
+pub fn measure<T>(f: impl FnOnce() -> T) -> (T, Usage) {
+    let start = CURRENT.load(Ordering::Relaxed);
+    PEAK.store(start, Ordering::Relaxed);
+    let total = TOTAL.load(Ordering::Relaxed);
+    let result = f();
+    let usage = Usage {
+        peak: PEAK.load(Ordering::Relaxed).saturating_sub(start),
+        allocated: TOTAL.load(Ordering::Relaxed) - total,
+    };
+    (result, usage)
+}
+
+/// A number of bytes in the largest binary unit keeping it above 1, e.g.
+/// `114.4 MiB`.
+pub fn format_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[global_allocator]
+    static ALLOCATOR: TrackingAllocator = TrackingAllocator;
+
+    #[test]
+    fn test_measure() {
+        // Other tests allocate at the same time, hence the lower bounds
+        let (sum, usage) = measure(|| {
+            let mut numbers: Vec<u64> = (0..1 << 17).collect();
+            numbers.push(0);
+            numbers.iter().sum::<u64>()
+        });
+        assert_eq!(sum, (1 << 16) * ((1 << 17) - 1));
+        assert!(usage.peak >= 8 << 17, "{:?}", usage);
+        assert!(usage.allocated >= 3 * (8 << 17), "{:?}", usage);
+    }
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(0), "0 B");
+        assert_eq!(format_bytes(1023), "1023 B");
+        assert_eq!(format_bytes(1536), "1.5 KiB");
+        assert_eq!(format_bytes(120_000_000), "114.4 MiB");
+        assert_eq!(format_bytes(3 << 30), "3.0 GiB");
+    }
+}
@@ -0,0 +1,212 @@
+use std::fmt::Write;
+
+use crate::error::{Error, ErrorKind};
+use crate::runner::DayReport;
+use crate::solution::Answer;
+
+const YEAR: u16 = 2020;
+
+pub const CSV_HEADER: &str = "year,day,part,answer,duration_ms,status,message";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+    Text,
+    /// One JSON object per line
+    Json,
+    /// Comma separated values, starting with `CSV_HEADER`
+    Csv,
+}
+
+/// The outcome of one part of a day, or of parsing its input when that failed.
+#[derive(Debug, PartialEq)]
+pub struct Record<'a> {
+    pub year: u16,
+    pub day: usize,
+    /// `None` for a failed parse
+    pub part: Option<u8>,
+    pub answer: Option<&'a Answer>,
+    pub duration_ms: f64,
+    pub status: &'static str,
+    pub message: Option<String>,
+}
+
+fn status(error: &Error) -> &'static str {
+    match error.kind() {
+        ErrorKind::Io(_) => "error",
+        ErrorKind::Parse(_) => "invalid_input",
+        ErrorKind::Unsolvable(_) => "unsolvable",
+    }
+}
+
+pub fn records(report: &DayReport) -> Vec<Record<'_>> {
+    let record = |part, answer, duration: std::time::Duration, error: Option<&Error>| Record {
+        year: YEAR,
+        day: report.day,
+        part,
+        answer,
+        duration_ms: duration.as_secs_f64() * 1000.0,
+        status: error.map_or("ok", status),
+        message: error.map(Error::to_string),
+    };
+    match &report.parts {
+        Ok(parts) => parts
+            .iter()
+            .map(|part| match &part.answer {
+                Ok(answer) => record(Some(part.part), Some(answer), part.time, None),
+                Err(error) => record(Some(part.part), None, part.time, Some(error)),
+            })
+            .collect(),
+        Err(error) => vec![record(None, None, report.parse_time, Some(error))],
+    }
+}
+
+pub(crate) fn json_string(s: &str) -> String {
+    let mut json = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if c.is_control() => write!(json, "\\u{:04x}", c as u32).unwrap(),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains(&[',', '"', '\n', '\r'][..]) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+/// Lays out `rows` in columns aligned on the left, 2 spaces apart.
+pub fn table(rows: &[Vec<String>]) -> String {
+    let mut widths: Vec<usize> = vec![];
+    for row in rows {
+        for (column, cell) in row.iter().enumerate() {
+            let width = cell.chars().count();
+            match widths.get_mut(column) {
+                Some(max) => *max = (*max).max(width),
+                None => widths.push(width),
+            }
+        }
+    }
+    let mut table = String::new();
+    for row in rows {
+        let mut line = String::new();
+        for (cell, width) in row.iter().zip(&widths) {
+            write!(line, "{:<width$}  ", cell, width = width).unwrap();
+        }
+        table.push_str(line.trim_end());
+        table.push('\n');
+    }
+    table
+}
+
+impl Record<'_> {
+    pub fn to_json(&self) -> String {
+        let optional = |value: Option<String>| value.unwrap_or_else(|| "null".to_string());
+        format!(
+            "{{\"year\":{},\"day\":{},\"part\":{},\"answer\":{},\"duration_ms\":{:.3},\"status\":{},\"message\":{}}}",
+            self.year,
+            self.day,
+            optional(self.part.map(|part| part.to_string())),
+            optional(self.answer.map(Answer::to_json)),
+            self.duration_ms,
+            json_string(self.status),
+            optional(self.message.as_deref().map(json_string)),
+        )
+    }
+
+    pub fn to_csv(&self) -> String {
+        format!(
+            "{},{},{},{},{:.3},{},{}",
+            self.year,
+            self.day,
+            self.part.map_or(String::new(), |part| part.to_string()),
+            csv_field(&self.answer.map_or(String::new(), Answer::to_string)),
+            self.duration_ms,
+            self.status,
+            csv_field(self.message.as_deref().unwrap_or("")),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::run_day;
+
+    #[test]
+    fn test_records() {
+        let report = run_day(1, Some(2), "1721\n979\n366\n299\n675\n1456");
+        let parts = records(&report);
+        assert_eq!(parts.len(), 1);
+        assert_eq!(parts[0].answer, Some(&Answer::Integer(241861950)));
+        assert!(parts[0].to_json().contains(r#""answer":241861950,"#));
+        assert_eq!(parts[0].status, "ok");
+
+        let report = run_day(1, None, "1721\nabc");
+        let parse = records(&report);
+        assert_eq!(parse.len(), 1);
+        assert_eq!(parse[0].part, None);
+        assert_eq!(parse[0].status, "invalid_input");
+    }
+
+    #[test]
+    fn test_table() {
+        let rows = vec![
+            vec![String::new(), "alice".to_string(), "bob".to_string()],
+            vec!["Day 1 part 1".to_string(), "1".to_string(), "-".to_string()],
+            vec!["Day 21 part 2".to_string(), "µs".to_string(), String::new()],
+        ];
+        assert_eq!(
+            table(&rows),
+            "               alice  bob\nDay 1 part 1   1      -\nDay 21 part 2  µs\n"
+        );
+    }
+
+    #[test]
+    fn test_serialize() {
+        let answer = Answer::from("mxmxvkd,sqjhc,fvjkl");
+        let record = Record {
+            year: 2020,
+            day: 21,
+            part: Some(2),
+            answer: Some(&answer),
+            duration_ms: 1.5,
+            status: "ok",
+            message: None,
+        };
+        assert_eq!(
+            record.to_json(),
+            r#"{"year":2020,"day":21,"part":2,"answer":"mxmxvkd,sqjhc,fvjkl","duration_ms":1.500,"status":"ok","message":null}"#
+        );
+        assert_eq!(
+            record.to_csv(),
+            r#"2020,21,2,"mxmxvkd,sqjhc,fvjkl",1.500,ok,"#
+        );
+
+        let record = Record {
+            part: None,
+            answer: None,
+            status: "invalid_input",
+            message: Some("line 2: invalid \"x\"".to_string()),
+            ..record
+        };
+        assert_eq!(
+            record.to_json(),
+            r#"{"year":2020,"day":21,"part":null,"answer":null,"duration_ms":1.500,"status":"invalid_input","message":"line 2: invalid \"x\""}"#
+        );
+        assert_eq!(
+            record.to_csv(),
+            r#"2020,21,,,1.500,invalid_input,"line 2: invalid ""x""""#
+        );
+    }
+}
@@ -0,0 +1,338 @@
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::cache::Cache;
+use crate::error::Result;
+use crate::memory::{self, Usage};
+use crate::solution::{Answer, SOLVERS};
+
+pub struct PartReport {
+    pub part: u8,
+    pub answer: Result<Answer>,
+    pub time: Duration,
+    pub memory: Usage,
+    /// Whether the answer was read from the cache rather than computed
+    pub cached: bool,
+}
+
+pub struct DayReport {
+    pub day: usize,
+    pub parse_time: Duration,
+    pub parse_memory: Usage,
+    /// The parts that were run, or the error found while parsing the input
+    pub parts: Result<Vec<PartReport>>,
+}
+
+impl DayReport {
+    pub fn succeeded(&self) -> bool {
+        match &self.parts {
+            Ok(parts) => parts.iter().all(|part| part.answer.is_ok()),
+            Err(_) => false,
+        }
+    }
+}
+
+fn track<T>(f: impl FnOnce() -> T) -> (T, Duration, Usage) {
+    let start = Instant::now();
+    let (result, memory) = memory::measure(f);
+    (result, start.elapsed(), memory)
+}
+
+/// Solves a day, or only `part` of it, timing the parse and every part and
+/// measuring their memory.
+pub fn run_day(day: usize, part: Option<u8>, input: &str) -> DayReport {
+    let (solution, parse_time, parse_memory) = track(|| SOLVERS[day - 1](input));
+    let parts = solution.map(|mut solution| {
+        let mut parts = vec![];
+        if part != Some(2) {
+            let (answer, time, memory) = track(|| solution.part_1());
+            parts.push(PartReport {
+                part: 1,
+                answer,
+                time,
+                memory,
+                cached: false,
+            });
+        }
+        if part != Some(1) {
+            let (answer, time, memory) = track(|| solution.part_2());
+            // Days without a second part have nothing to report
+            if !matches!(answer, Ok(Answer::None)) {
+                parts.push(PartReport {
+                    part: 2,
+                    answer,
+                    time,
+                    memory,
+                    cached: false,
+                });
+            }
+        }
+        parts
+    });
+    DayReport {
+        day,
+        parse_time,
+        parse_memory,
+        parts,
+    }
+}
+
+/// Like `run_day`, but answers every part from `cache` when all of them are
+/// there, without parsing the input. Otherwise the day is solved and its
+/// answers are cached.
+pub fn run_day_cached(day: usize, part: Option<u8>, input: &str, cache: &Cache) -> DayReport {
+    let wanted = match part {
+        Some(part) => vec![part],
+        None => vec![1, 2],
+    };
+    let cached: Option<Vec<(u8, Answer)>> = wanted
+        .iter()
+        .map(|&part| cache.get(day, part, input).map(|answer| (part, answer)))
+        .collect();
+    if let Some(cached) = cached {
+        let parts = cached
+            .into_iter()
+            .filter(|(_, answer)| *answer != Answer::None)
+            .map(|(part, answer)| PartReport {
+                part,
+                answer: Ok(answer),
+                time: Duration::ZERO,
+                memory: Usage::default(),
+                cached: true,
+            })
+            .collect();
+        return DayReport {
+            day,
+            parse_time: Duration::ZERO,
+            parse_memory: Usage::default(),
+            parts: Ok(parts),
+        };
+    }
+
+    let report = run_day(day, part, input);
+    if let Ok(parts) = &report.parts {
+        for part in wanted {
+            let answer = match parts.iter().find(|report| report.part == part) {
+                Some(PartReport {
+                    answer: Ok(answer), ..
+                }) => answer,
+                Some(_) => continue,
+                // Not reported since the day has no such part
+                None => &Answer::None,
+            };
+            // A cache that cannot be written only costs the next run its time
+            let _ = cache.insert(day, part, input, answer);
+        }
+    }
+    report
+}
+
+/// Runs the given `(day, input)` pairs on `jobs` threads. The reports are
+/// handed to `on_report` in the order of `inputs`, as soon as every earlier
+/// day is done. Answers are looked up in `cache` first, when given.
+pub fn run_days<F>(
+    inputs: &[(usize, String)],
+    part: Option<u8>,
+    jobs: usize,
+    cache: Option<&Cache>,
+    mut on_report: F,
+) where
+    F: FnMut(DayReport),
+{
+    let run = |day: usize, input: &str| match cache {
+        Some(cache) => run_day_cached(day, part, input, cache),
+        None => run_day(day, part, input),
+    };
+    if jobs <= 1 {
+        for (day, input) in inputs {
+            on_report(run(*day, input));
+        }
+        return;
+    }
+
+    let next = AtomicUsize::new(0);
+    let (sender, receiver) = mpsc::channel();
+    thread::scope(|scope| {
+        for _ in 0..jobs.min(inputs.len()) {
+            let sender = sender.clone();
+            let next = &next;
+            scope.spawn(move || loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let (day, input) = match inputs.get(index) {
+                    Some(day) => day,
+                    None => break,
+                };
+                if sender.send((index, run(*day, input))).is_err() {
+                    break;
+                }
+            });
+        }
+        drop(sender);
+
+        // Reports arriving early wait for the days before them
+        let mut pending = BTreeMap::new();
+        let mut waiting = 0;
+        for (index, report) in receiver {
+            pending.insert(index, report);
+            while let Some(report) = pending.remove(&waiting) {
+                on_report(report);
+                waiting += 1;
+            }
+        }
+    });
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Stats {
+    pub min: Duration,
+    pub median: Duration,
+    pub mean: Duration,
+}
+
+impl Stats {
+    /// Returns `None` when there is no sample.
+    pub fn new(samples: &mut [Duration]) -> Option<Stats> {
+        if samples.is_empty() {
+            return None;
+        }
+        samples.sort();
+        let middle = samples.len() / 2;
+        let median = if samples.len().is_multiple_of(2) {
+            (samples[middle - 1] + samples[middle]) / 2
+        } else {
+            samples[middle]
+        };
+        Some(Stats {
+            min: samples[0],
+            median,
+            mean: samples.iter().sum::<Duration>() / samples.len() as u32,
+        })
+    }
+}
+
+pub struct BenchReport {
+    pub day: usize,
+    pub parse: Stats,
+    /// Timings of each part, by part number
+    pub parts: Vec<(u8, Stats)>,
+}
+
+/// Runs a day `runs` times, at least once, parsing the input again every time.
+/// Fails with the first error met, since a failing day has no meaningful
+/// timing.
+pub fn bench_day(day: usize, part: Option<u8>, input: &str, runs: usize) -> Result<BenchReport> {
+    let mut parse_times = vec![];
+    let mut part_times: Vec<(u8, Vec<Duration>)> = vec![];
+    for _ in 0..runs {
+        let report = run_day(day, part, input);
+        parse_times.push(report.parse_time);
+        for (index, part) in report.parts?.into_iter().enumerate() {
+            part.answer?;
+            if index == part_times.len() {
+                part_times.push((part.part, vec![]));
+            }
+            part_times[index].1.push(part.time);
+        }
+    }
+    let stats = |samples: &mut [Duration]| Stats::new(samples).expect("at least one run");
+    Ok(BenchReport {
+        day,
+        parse: stats(&mut parse_times),
+        parts: part_times
+            .into_iter()
+            .map(|(part, mut times)| (part, stats(&mut times)))
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stats() {
+        let ms = Duration::from_millis;
+        assert_eq!(
+            Stats::new(&mut [ms(4), ms(1), ms(7)]),
+            Some(Stats {
+                min: ms(1),
+                median: ms(4),
+                mean: ms(4),
+            })
+        );
+        assert_eq!(
+            Stats::new(&mut [ms(3), ms(1), ms(2), ms(10)])
+                .unwrap()
+                .median,
+            ms(2) + Duration::from_micros(500)
+        );
+        assert_eq!(Stats::new(&mut []), None);
+    }
+
+    #[test]
+    fn test_run_day() {
+        let report = run_day(1, None, "1721\n979\n366\n299\n675\n1456");
+        let parts = report.parts.unwrap();
+        assert_eq!(parts.len(), 2);
+        assert_eq!(
+            parts[1].answer.as_ref().unwrap(),
+            &Answer::Integer(241861950)
+        );
+
+        assert!(!run_day(1, Some(1), "1721\nx").succeeded());
+        assert_eq!(
+            run_day(25, None, "5764801\n17807724").parts.unwrap().len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_run_day_cached() {
+        let dir = std::env::temp_dir().join(format!("aoc2020-runner-test-{}", std::process::id()));
+        let cache = Cache::new(&dir);
+        let input = "1721\n979\n366\n299\n675\n1456";
+        let report = run_day_cached(1, Some(1), input, &cache);
+        assert!(!report.parts.unwrap()[0].cached);
+        let report = run_day_cached(1, Some(1), input, &cache);
+        let parts = report.parts.unwrap();
+        assert!(parts[0].cached);
+        assert_eq!(parts[0].answer.as_ref().unwrap(), &Answer::Integer(514579));
+        // Part 2 was never computed
+        assert!(!run_day_cached(1, None, input, &cache).parts.unwrap()[1].cached);
+
+        // Day 25 has no second part to report, even from the cache
+        let input = "5764801\n17807724";
+        run_day_cached(25, None, input, &cache);
+        let parts = run_day_cached(25, None, input, &cache).parts.unwrap();
+        assert_eq!(parts.len(), 1);
+        assert!(parts[0].cached);
+
+        assert!(!run_day_cached(1, Some(1), "1721\nx", &cache).succeeded());
+        assert!(!run_day_cached(1, Some(1), "1721\nx", &cache).succeeded());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_run_days() {
+        let inputs = vec![
+            (25, "5764801\n17807724".to_string()),
+            (1, "1721\n979\n366\n299\n675\n1456".to_string()),
+            (
+                2,
+                "1-3 a: abcde\n1-3 b: cdefg\n2-9 c: ccccccccc".to_string(),
+            ),
+            (1, "1721\nx".to_string()),
+        ];
+        for &jobs in &[1, 3] {
+            let mut reports = vec![];
+            run_days(&inputs, Some(1), jobs, None, |report| reports.push(report));
+            let days: Vec<usize> = reports.iter().map(|report| report.day).collect();
+            assert_eq!(days, vec![25, 1, 2, 1]);
+            assert!(reports[2].succeeded());
+            assert!(!reports[3].succeeded());
+        }
+    }
+}
@@ -0,0 +1,141 @@
+use std::convert::TryFrom;
+use std::fmt;
+
+use crate::error::Result;
+use crate::output::json_string;
+use crate::{
+    day1, day10, day11, day12, day13, day14, day15, day16, day17, day18, day19, day2, day20, day21,
+    day22, day23, day24, day25, day3, day4, day5, day6, day7, day8, day9,
+};
+
+/// The answer of a part.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Answer {
+    Integer(i64),
+    /// An integer out of the range of `i64`
+    BigInteger(i128),
+    String(String),
+    /// The answer of a part that does not exist, such as day 25 part 2
+    None,
+}
+
+impl Answer {
+    /// Integers are JSON numbers, `None` is `null`.
+    pub fn to_json(&self) -> String {
+        match self {
+            Answer::Integer(integer) => integer.to_string(),
+            Answer::BigInteger(integer) => integer.to_string(),
+            Answer::String(string) => json_string(string),
+            Answer::None => "null".to_string(),
+        }
+    }
+}
+
+/// `None` is displayed as an empty string.
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Answer::Integer(integer) => integer.fmt(f),
+            Answer::BigInteger(integer) => integer.fmt(f),
+            Answer::String(string) => string.fmt(f),
+            Answer::None => Ok(()),
+        }
+    }
+}
+
+macro_rules! integer_answer {
+    ($($integer:ty),*) => {
+        $(
+            impl From<$integer> for Answer {
+                fn from(integer: $integer) -> Self {
+                    match i64::try_from(integer) {
+                        Ok(integer) => Answer::Integer(integer),
+                        Err(_) => Answer::BigInteger(integer as i128),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+integer_answer!(i32, u32, i64, u64, usize, i128);
+
+impl From<String> for Answer {
+    fn from(string: String) -> Self {
+        Answer::String(string)
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(string: &str) -> Self {
+        Answer::String(string.to_string())
+    }
+}
+
+/// A puzzle solver: the input is parsed once, then each part computes its
+/// answer from the parsed state.
+pub trait Solution {
+    fn parse(input: &str) -> Result<Self>
+    where
+        Self: Sized;
+
+    fn part_1(&mut self) -> Result<Answer>;
+
+    /// Only day 25 has no second part.
+    fn part_2(&mut self) -> Result<Answer> {
+        Ok(Answer::None)
+    }
+}
+
+pub type Solver = fn(&str) -> Result<Box<dyn Solution>>;
+
+fn solver<S: Solution + 'static>(input: &str) -> Result<Box<dyn Solution>> {
+    Ok(Box::new(S::parse(input)?))
+}
+
+/// Solvers of every day, indexed by `day - 1`.
+pub const SOLVERS: [Solver; 25] = [
+    solver::<day1::Day1>,
+    solver::<day2::Day2>,
+    solver::<day3::Day3>,
+    solver::<day4::Day4>,
+    solver::<day5::Day5>,
+    solver::<day6::Day6>,
+    solver::<day7::Day7>,
+    solver::<day8::Day8>,
+    solver::<day9::Day9>,
+    solver::<day10::Day10>,
+    solver::<day11::Day11>,
+    solver::<day12::Day12>,
+    solver::<day13::Day13>,
+    solver::<day14::Day14>,
+    solver::<day15::Day15>,
+    solver::<day16::Day16>,
+    solver::<day17::Day17>,
+    solver::<day18::Day18>,
+    solver::<day19::Day19>,
+    solver::<day20::Day20>,
+    solver::<day21::Day21>,
+    solver::<day22::Day22>,
+    solver::<day23::Day23>,
+    solver::<day24::Day24>,
+    solver::<day25::Day25>,
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_answer() {
+        assert_eq!(Answer::from(514579), Answer::Integer(514579));
+        assert_eq!(Answer::from(u64::MAX), Answer::BigInteger(u64::MAX as i128));
+        assert_eq!(Answer::from(u64::MAX).to_string(), "18446744073709551615");
+        assert_eq!(Answer::from("mxmxvkd,sqjhc").to_string(), "mxmxvkd,sqjhc");
+        assert_eq!(Answer::None.to_string(), "");
+
+        assert_eq!(Answer::from(-3i64).to_json(), "-3");
+        assert_eq!(Answer::from("a\"b").to_json(), r#""a\"b""#);
+        assert_eq!(Answer::None.to_json(), "null");
+    }
+}
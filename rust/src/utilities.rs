@@ -1,32 +1,168 @@
-use std::fmt::Debug;
-use std::fs;
+use std::fmt::Display;
 use std::str::FromStr;
 
-fn read_file(file_name: &str) -> String {
-    fs::read_to_string(file_name).expect("File does not exist")
+use crate::error::{Error, Result};
+
+/// Parses `token`, a slice of `line`, reporting its column on failure.
+pub fn parse_number<T>(line: &str, token: &str) -> Result<T>
+where
+    T: FromStr,
+    <T as FromStr>::Err: Display,
+{
+    token.trim().parse().map_err(|error| {
+        Error::parse(format!("invalid number `{}`: {}", token, error)).at(line, token)
+    })
+}
+
+/// Splits `token`, a slice of `line`, around the first `separator`.
+pub fn split_pair<'a>(line: &str, token: &'a str, separator: &str) -> Result<(&'a str, &'a str)> {
+    token.split_once(separator).ok_or_else(|| {
+        Error::parse(format!("expected `{}` in `{}`", separator, token)).at(line, token)
+    })
+}
+
+/// Removes the line breaks ending the input, along with their `\r`.
+fn trim_newlines(input: &str) -> &str {
+    input.trim_end_matches(&['\r', '\n'][..])
+}
+
+/// Iterates over the lines of the input, without their `\n` or `\r\n`.
+/// Line breaks at the end of the input do not start any line.
+pub fn lines(input: &str) -> impl Iterator<Item = &str> {
+    trim_newlines(input)
+        .split_terminator('\n')
+        .map(|line| line.strip_suffix('\r').unwrap_or(line))
 }
 
-pub fn read_lines(file_name: &str) -> Vec<String> {
-    let content = read_file(file_name);
-    content.split('\n').map(|line| line.to_string()).collect()
+/// Splits off the first line of the input, returning it along with the rest.
+pub fn split_first_line(input: &str) -> Option<(&str, &str)> {
+    let (first, rest) = input.split_once('\n')?;
+    Some((first.strip_suffix('\r').unwrap_or(first), rest))
 }
 
-pub fn read_int_array<T>(file_name: &str) -> Vec<T>
+/// Parses every line with `parse`, adding the line number to any error.
+pub fn map_lines<T, F>(input: &str, mut parse: F) -> Result<Vec<T>>
 where
-    T: FromStr,
-    <T as FromStr>::Err: Debug,
+    F: FnMut(&str) -> Result<T>,
 {
-    let content = read_file(file_name);
-    content
-        .split('\n')
-        .map(|num| num.parse::<T>().unwrap())
+    lines(input)
+        .enumerate()
+        .map(|(index, line)| parse(line).map_err(|error| error.on_line(index + 1)))
         .collect()
 }
 
-pub fn read_block(file_name: &str) -> Vec<String> {
-    let content = read_file(file_name);
-    content
-        .split("\n\n")
-        .map(|block| block.to_string())
+/// Parses every line as a `T`.
+pub fn parse_lines<T>(input: &str) -> Result<Vec<T>>
+where
+    T: FromStr,
+    <T as FromStr>::Err: Display,
+{
+    map_lines(input, |line| parse_number(line, line))
+}
+
+/// Iterator over the blocks of an input, see `numbered_blocks`.
+pub struct Blocks<'a> {
+    rest: &'a str,
+    line: usize,
+}
+
+impl<'a> Iterator for Blocks<'a> {
+    type Item = (usize, &'a str);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // Skip the blank lines separating blocks
+        loop {
+            if self.rest.is_empty() {
+                return None;
+            }
+            let (line, rest) = self.rest.split_once('\n').unwrap_or((self.rest, ""));
+            if !line.strip_suffix('\r').unwrap_or(line).is_empty() {
+                break;
+            }
+            self.rest = rest;
+            self.line += 1;
+        }
+        let offset = self.line;
+        let mut end = 0;
+        for line in self.rest.split_inclusive('\n') {
+            if line.trim_end_matches(&['\r', '\n'][..]).is_empty() {
+                break;
+            }
+            end += line.len();
+            self.line += 1;
+        }
+        let block = trim_newlines(&self.rest[..end]);
+        self.rest = &self.rest[end..];
+        Some((offset, block))
+    }
+}
+
+/// Iterates over the blocks of the input, which are separated by blank lines,
+/// each paired with the number of lines before it.
+pub fn numbered_blocks(input: &str) -> Blocks<'_> {
+    Blocks {
+        rest: input,
+        line: 0,
+    }
+}
+
+/// Iterates over the blocks of the input, see `numbered_blocks`.
+pub fn blocks(input: &str) -> impl Iterator<Item = &str> {
+    numbered_blocks(input).map(|(_, block)| block)
+}
+
+/// Parses a block found `offset` lines into the input. Errors carrying a line
+/// relative to the block are shifted to the line of the whole input.
+pub fn map_block<T, F>(offset: usize, block: &str, parse: F) -> Result<T>
+where
+    F: FnOnce(&str) -> Result<T>,
+{
+    parse(block).map_err(|error| error.shift_lines(offset).on_line(offset + 1))
+}
+
+/// Parses every block with `parse`, see `map_block`.
+pub fn map_blocks<T, F>(input: &str, mut parse: F) -> Result<Vec<T>>
+where
+    F: FnMut(&str) -> Result<T>,
+{
+    numbered_blocks(input)
+        .map(|(offset, block)| map_block(offset, block, &mut parse))
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lines() {
+        let input = "a\r\nb\n\nc\r\n\r\n";
+        assert_eq!(lines(input).collect::<Vec<_>>(), vec!["a", "b", "", "c"]);
+        assert_eq!(lines("").count(), 0);
+        assert_eq!(split_first_line("header\r\na\nb"), Some(("header", "a\nb")));
+        assert_eq!(split_first_line("header"), None);
+    }
+
+    #[test]
+    fn test_blocks() {
+        let input = "a\r\nb\r\n\r\n\r\nc\n\nd\ne\n";
+        assert_eq!(
+            numbered_blocks(input).collect::<Vec<_>>(),
+            vec![(0, "a\r\nb"), (4, "c"), (6, "d\ne")]
+        );
+        assert_eq!(blocks("\n\na\n").collect::<Vec<_>>(), vec!["a"]);
+        assert_eq!(blocks("\r\n").count(), 0);
+    }
+
+    #[test]
+    fn test_parse_lines() {
+        assert_eq!(parse_lines::<u32>("1\r\n2\n3\n").unwrap(), vec![1, 2, 3]);
+        let error = parse_lines::<u32>("1\n\n3").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 2, column 1: invalid number ``: cannot parse integer from empty string"
+        );
+        let error = map_blocks("1\n2\n\n3\nx", parse_lines::<u32>).unwrap_err();
+        assert_eq!(error.line(), Some(5));
+    }
+}
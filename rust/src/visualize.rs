@@ -0,0 +1,173 @@
+use std::fmt::Write;
+
+use crate::automaton;
+use crate::day20::{self, Pixel};
+use crate::error::Result;
+use crate::grid::Grid;
+
+/// Days that can be watched, see `frames`.
+pub const DAYS: [usize; 4] = [11, 17, 20, 24];
+
+/// Generations after which a universe is shown no more, even if it still
+/// changes.
+const MAX_GENERATIONS: usize = 1000;
+
+pub type Rgb = [u8; 3];
+
+/// Colours of the cells of an automaton.
+struct Palette {
+    hole: Rgb,
+    dead: Rgb,
+    alive: Rgb,
+}
+
+fn palette(day: usize) -> Palette {
+    match day {
+        // Floor, empty seats and occupied seats
+        11 => Palette {
+            hole: [40, 30, 20],
+            dead: [110, 110, 110],
+            alive: [240, 200, 40],
+        },
+        // White and black tiles
+        24 => Palette {
+            hole: [0, 0, 0],
+            dead: [230, 230, 230],
+            alive: [20, 20, 20],
+        },
+        _ => Palette {
+            hole: [0, 0, 0],
+            dead: [20, 20, 40],
+            alive: [80, 220, 240],
+        },
+    }
+}
+
+fn paint(cells: &Grid<Option<bool>>, palette: &Palette) -> Grid<Rgb> {
+    cells.map(|cell| match cell {
+        None => palette.hole,
+        Some(false) => palette.dead,
+        Some(true) => palette.alive,
+    })
+}
+
+fn paint_pixel(pixel: &Pixel) -> Rgb {
+    match pixel {
+        Pixel::Missing => [0, 0, 0],
+        Pixel::Water => [20, 60, 140],
+        Pixel::Rough => [140, 200, 240],
+        Pixel::Monster => [60, 220, 80],
+    }
+}
+
+/// Calls `on_frame` with every step of solving `part` of one of `DAYS`: each
+/// generation of the automata of days 11, 17 (the slice of the initial
+/// cubes) and 24, and each tile placed by day 20.
+///
+/// # Panics
+///
+/// Panics when the day is not one of `DAYS`.
+pub fn frames<F>(day: usize, part: u8, input: &str, mut on_frame: F) -> Result<()>
+where
+    F: FnMut(Grid<Rgb>) -> Result<()>,
+{
+    if day == 20 {
+        for frame in day20::assembly(input)? {
+            on_frame(frame.map(paint_pixel))?;
+        }
+        return Ok(());
+    }
+
+    let mut automaton = automaton::automaton(day, part, input)?;
+    let palette = palette(day);
+    let universe = &mut automaton.universe;
+    on_frame(paint(&universe.picture(), &palette))?;
+    for _ in 0..automaton.generations.unwrap_or(MAX_GENERATIONS) {
+        let changed = universe.step(&automaton.rule);
+        if !changed && automaton.generations.is_none() {
+            break;
+        }
+        on_frame(paint(&universe.picture(), &palette))?;
+    }
+    Ok(())
+}
+
+/// Draws the image with ANSI colours, two rows of pixels per line of text.
+pub fn to_ansi(image: &Grid<Rgb>) -> String {
+    let mut text = String::new();
+    for row in (0..image.height()).step_by(2) {
+        for col in 0..image.width() {
+            let [r, g, b] = image[(row, col)];
+            write!(text, "\x1b[38;2;{};{};{}m", r, g, b).unwrap();
+            if let Some([r, g, b]) = image.get((row + 1, col)) {
+                write!(text, "\x1b[48;2;{};{};{}m", r, g, b).unwrap();
+            } else {
+                text.push_str("\x1b[49m");
+            }
+            text.push('▀');
+        }
+        text.push_str("\x1b[0m\n");
+    }
+    text
+}
+
+/// Encodes the image as a binary PPM, each pixel becoming a square of `scale`
+/// pixels.
+pub fn to_ppm(image: &Grid<Rgb>, scale: usize) -> Vec<u8> {
+    let mut ppm = format!(
+        "P6\n{} {}\n255\n",
+        image.width() * scale,
+        image.height() * scale
+    )
+    .into_bytes();
+    for row in image.rows() {
+        for _ in 0..scale {
+            for pixel in row {
+                for _ in 0..scale {
+                    ppm.extend_from_slice(pixel);
+                }
+            }
+        }
+    }
+    ppm
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode() {
+        let image =
+            Grid::from_rows(vec![vec![[255, 0, 0]], vec![[0, 0, 255]], vec![[1, 2, 3]]]).unwrap();
+        assert_eq!(
+            to_ansi(&image),
+            "\x1b[38;2;255;0;0m\x1b[48;2;0;0;255m▀\x1b[0m\n\x1b[38;2;1;2;3m\x1b[49m▀\x1b[0m\n"
+        );
+        let ppm = to_ppm(&image, 2);
+        assert!(ppm.starts_with(b"P6\n2 6\n255\n"));
+        assert_eq!(ppm.len(), 11 + 2 * 6 * 3);
+        assert_eq!(&ppm[11..17], &[255, 0, 0, 255, 0, 0]);
+    }
+
+    #[test]
+    fn test_frames() {
+        let mut sizes = vec![];
+        frames(17, 1, ".#.\n..#\n###", |frame| {
+            sizes.push((frame.width(), frame.height()));
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(sizes.len(), 7);
+        assert_eq!(sizes[0], (3, 3));
+
+        let mut count = 0;
+        frames(11, 1, "L.L\nLLL", |_| {
+            count += 1;
+            Ok(())
+        })
+        .unwrap();
+        // Every seat gets taken, then the middle one is left
+        assert_eq!(count, 3);
+    }
+}
@@ -0,0 +1,96 @@
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use crate::answers::Answers;
+use crate::error::Result;
+
+/// Name of the user whose inputs lie at the root of the workspace.
+pub const DEFAULT_USER: &str = "default";
+
+/// The inputs of one user: a directory of `day<N>.txt` files, next to their
+/// expected answers in `answers.toml` when known.
+#[derive(Debug, PartialEq)]
+pub struct User {
+    pub name: String,
+    pub dir: PathBuf,
+}
+
+impl User {
+    /// The input of `day`, or `None` when the user has none.
+    pub fn input(&self, day: usize) -> Result<Option<String>> {
+        match fs::read_to_string(self.dir.join(format!("day{}.txt", day))) {
+            Ok(input) => Ok(Some(input)),
+            Err(error) if error.kind() == ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    /// The expected answers of the user, empty without an `answers.toml`.
+    pub fn answers(&self) -> Result<Answers> {
+        match fs::read_to_string(self.dir.join("answers.toml")) {
+            Ok(answers) => Answers::parse(&answers),
+            Err(error) if error.kind() == ErrorKind::NotFound => Ok(Answers::default()),
+            Err(error) => Err(error.into()),
+        }
+    }
+}
+
+fn has_inputs(dir: &Path) -> bool {
+    (1..=25).any(|day| dir.join(format!("day{}.txt", day)).is_file())
+}
+
+/// The users of a workspace laid out as `<dir>/<user>/day<N>.txt`, sorted by
+/// name. Inputs right in `dir` belong to `DEFAULT_USER`, listed first.
+pub fn users(dir: &Path) -> Result<Vec<User>> {
+    let mut users = vec![];
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() && has_inputs(&path) {
+            users.push(User {
+                name: path.file_name().unwrap().to_string_lossy().into_owned(),
+                dir: path,
+            });
+        }
+    }
+    users.sort_by(|a, b| a.name.cmp(&b.name));
+    if has_inputs(dir) {
+        users.insert(
+            0,
+            User {
+                name: DEFAULT_USER.to_string(),
+                dir: dir.to_path_buf(),
+            },
+        );
+    }
+    Ok(users)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{env, process};
+
+    #[test]
+    fn test_users() {
+        let dir = env::temp_dir().join(format!("aoc2020-workspace-test-{}", process::id()));
+        for path in ["bob", "alice", "empty", "notes"] {
+            fs::create_dir_all(dir.join(path)).unwrap();
+        }
+        fs::write(dir.join("day1.txt"), "1721").unwrap();
+        fs::write(dir.join("bob/day25.txt"), "5764801\n17807724").unwrap();
+        fs::write(dir.join("alice/day1.txt"), "979").unwrap();
+        fs::write(dir.join("alice/answers.toml"), "[day1]\npart1 = 1").unwrap();
+        fs::write(dir.join("notes/todo.txt"), "").unwrap();
+
+        let users = users(&dir).unwrap();
+        let names: Vec<&str> = users.iter().map(|user| user.name.as_str()).collect();
+        assert_eq!(names, vec![DEFAULT_USER, "alice", "bob"]);
+        assert_eq!(users[1].input(1).unwrap().as_deref(), Some("979"));
+        assert_eq!(users[1].input(2).unwrap(), None);
+        assert_eq!(users[1].answers().unwrap().get(1, 1), Some("1"));
+        assert_eq!(users[2].answers().unwrap().get(1, 1), None);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,195 @@
+//! Cross-checks the Rust solutions against the Python ones of `python/`. Both
+//! are fed the same inputs, the one of `inputs/` when present and a few
+//! generated ones, and must find the same answers.
+//!
+//! The Python scripts read `../inputs/day<N>.txt`, so they are run from a
+//! scratch directory laid out the same way. The interpreter is `python3`, or
+//! the one named by `PYTHON`; the tests are skipped when it is missing.
+//!
+//! Days 15 and 23 take a while in Python and in unoptimized Rust, run them
+//! with `cargo test --release -- --ignored`.
+
+use std::env;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::process::{self, Command};
+
+use aoc2020::generate;
+use aoc2020::runner;
+
+const SEEDS: [u64; 2] = [0, 1];
+
+/// Drivers of the days whose script embeds its input, calling into the
+/// script with the input of the scratch directory instead.
+fn driver(day: usize) -> Option<&'static str> {
+    match day {
+        15 => Some(
+            "from utilities import read_input
+from day15 import memory_game
+numbers = [int(n) for n in read_input('../inputs/day15.txt').strip().split(',')]
+print('\\tPart 1: {}'.format(memory_game(numbers, 2020)))
+print('\\tPart 2: {}'.format(memory_game(numbers, 30000000)))",
+        ),
+        23 => Some(
+            "from utilities import read_input
+from day23 import solve_part_1, solve_part_2
+initial = read_input('../inputs/day23.txt').strip()
+print('\\tPart 1: {}'.format(solve_part_1(initial, 100)))
+print('\\tPart 2: {}'.format(solve_part_2(initial)))",
+        ),
+        25 => Some(
+            "from utilities import read_int_input
+from day25 import solve_part_1
+public_card, public_door = read_int_input('../inputs/day25.txt')
+print('\\tPart 1: {}'.format(solve_part_1(public_card, public_door)))",
+        ),
+        _ => None,
+    }
+}
+
+/// The answers printed by the Python solution of `day`, or `None` without
+/// an interpreter.
+fn run_python(day: usize, scratch: &Path) -> Option<Vec<(u8, String)>> {
+    let python_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../python");
+    let mut command = Command::new(env::var("PYTHON").unwrap_or_else(|_| "python3".to_string()));
+    match driver(day) {
+        Some(driver) => command.arg("-c").arg(driver),
+        None => command.arg(python_dir.join(format!("day{}.py", day))),
+    };
+    let output = match command
+        .current_dir(scratch.join("python"))
+        .env("PYTHONPATH", &python_dir)
+        .output()
+    {
+        Ok(output) => output,
+        Err(error) if error.kind() == ErrorKind::NotFound => return None,
+        Err(error) => panic!("Cannot run Python: {}", error),
+    };
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        output.status.success(),
+        "Day {}: Python failed with {}\n{}{}",
+        day,
+        output.status,
+        stdout,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let answers = stdout
+        .lines()
+        .filter_map(|line| {
+            let (part, answer) = line.trim().strip_prefix("Part ")?.split_once(": ")?;
+            Some((part.parse().ok()?, answer.to_string()))
+        })
+        .collect();
+    Some(answers)
+}
+
+fn run_rust(day: usize, input: &str) -> Vec<(u8, String)> {
+    let report = runner::run_day(day, None, input);
+    let parts = report
+        .parts
+        .unwrap_or_else(|error| panic!("Day {}: cannot parse the input: {}", day, error));
+    parts
+        .into_iter()
+        .map(|report| {
+            let part = report.part;
+            let answer = report
+                .answer
+                .unwrap_or_else(|error| panic!("Day {}, part {}: {}", day, part, error));
+            (part, answer.to_string())
+        })
+        .collect()
+}
+
+fn check_differential(day: usize) {
+    // Without the final newline, like the files of `inputs/`: some scripts
+    // would take it for an empty record
+    let mut inputs: Vec<(String, String)> = SEEDS
+        .iter()
+        .map(|&seed| {
+            let input = generate::generate(day, seed);
+            (format!("seed {}", seed), input.trim_end().to_string())
+        })
+        .collect();
+    let fixture = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../inputs")
+        .join(format!("day{}.txt", day));
+    if let Ok(input) = fs::read_to_string(&fixture) {
+        inputs.push((fixture.display().to_string(), input));
+    }
+
+    let scratch = Scratch::new(day);
+    for (name, input) in inputs {
+        fs::write(scratch.0.join(format!("inputs/day{}.txt", day)), &input).unwrap();
+        let python = match run_python(day, &scratch.0) {
+            Some(answers) => answers,
+            None => {
+                eprintln!("Python is not available, skipping day {}", day);
+                return;
+            }
+        };
+        assert_eq!(run_rust(day, &input), python, "Day {} on {}", day, name);
+    }
+}
+
+/// A scratch directory holding `inputs/` and an empty `python/` to run from,
+/// removed on drop.
+struct Scratch(PathBuf);
+
+impl Scratch {
+    fn new(day: usize) -> Self {
+        let path = env::temp_dir().join(format!("aoc2020-differential-{}-{}", process::id(), day));
+        fs::create_dir_all(path.join("inputs")).unwrap();
+        fs::create_dir_all(path.join("python")).unwrap();
+        Scratch(path)
+    }
+}
+
+impl Drop for Scratch {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+macro_rules! differential {
+    ($($(#[$attr:meta])* $name:ident: $day:expr,)*) => {
+        $(
+            #[test]
+            $(#[$attr])*
+            fn $name() {
+                check_differential($day);
+            }
+        )*
+    };
+}
+
+differential! {
+    day1: 1,
+    day2: 2,
+    day3: 3,
+    day4: 4,
+    day5: 5,
+    day6: 6,
+    day7: 7,
+    day8: 8,
+    day9: 9,
+    day10: 10,
+    day11: 11,
+    day12: 12,
+    day13: 13,
+    day14: 14,
+    #[ignore]
+    day15: 15,
+    day16: 16,
+    day17: 17,
+    day18: 18,
+    day19: 19,
+    day20: 20,
+    day21: 21,
+    day22: 22,
+    #[ignore]
+    day23: 23,
+    day24: 24,
+    day25: 25,
+}
@@ -0,0 +1,106 @@
+//! Runs every example of `tests/examples/day<N>`. An example is an input file
+//! `<name>.txt` next to `<name>.answers`, which lists the expected answers
+//! in the format of `inputs/answers.toml`, without the table:
+//!
+//! ```toml
+//! part1 = 514579
+//! part2 = 241861950
+//! ```
+//!
+//! Parts left out of the answers are not run.
+
+use std::fs;
+use std::path::Path;
+
+use aoc2020::answers::{self, Answers, Verdict};
+use aoc2020::runner;
+
+fn check_examples(day: usize) {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/examples")
+        .join(format!("day{}", day));
+    let mut inputs: Vec<_> = fs::read_dir(&dir)
+        .unwrap_or_else(|error| panic!("Cannot read {}: {}", dir.display(), error))
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension() == Some("txt".as_ref()))
+        .collect();
+    inputs.sort();
+    assert!(!inputs.is_empty(), "Day {} has no example", day);
+
+    for path in inputs {
+        let name = path.file_stem().unwrap().to_string_lossy();
+        let input = fs::read_to_string(&path).unwrap();
+        let answers = fs::read_to_string(path.with_extension("answers"))
+            .map_err(|error| error.to_string())
+            .and_then(|answers| {
+                Answers::parse_day(day, &answers).map_err(|error| error.to_string())
+            })
+            .unwrap_or_else(|error| panic!("Day {} `{}`: invalid answers: {}", day, name, error));
+        let part = match (answers.get(day, 1), answers.get(day, 2)) {
+            (Some(_), Some(_)) => None,
+            (Some(_), None) => Some(1),
+            (None, Some(_)) => Some(2),
+            (None, None) => panic!("Day {} `{}` has no answer", day, name),
+        };
+
+        let report = runner::run_day(day, part, &input);
+        let verdicts = answers::verify(&report, &answers);
+        let expected = if part.is_none() { 2 } else { 1 };
+        assert_eq!(
+            verdicts.len(),
+            expected,
+            "Day {} `{}` does not have the parts given in its answers",
+            day,
+            name
+        );
+        for (part, verdict) in verdicts {
+            assert_eq!(
+                verdict,
+                Verdict::Pass,
+                "Day {} `{}`, part {:?}",
+                day,
+                name,
+                part
+            );
+        }
+    }
+}
+
+macro_rules! examples {
+    ($($name:ident: $day:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                check_examples($day);
+            }
+        )*
+    };
+}
+
+examples! {
+    day1: 1,
+    day2: 2,
+    day3: 3,
+    day4: 4,
+    day5: 5,
+    day6: 6,
+    day7: 7,
+    day8: 8,
+    day9: 9,
+    day10: 10,
+    day11: 11,
+    day12: 12,
+    day13: 13,
+    day14: 14,
+    day15: 15,
+    day16: 16,
+    day17: 17,
+    day18: 18,
+    day19: 19,
+    day20: 20,
+    day21: 21,
+    day22: 22,
+    day23: 23,
+    day24: 24,
+    day25: 25,
+}